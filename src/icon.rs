@@ -7,13 +7,13 @@ extern crate build_cfg;
 #[cfg(feature = "embed_resource")]
 extern crate embed_resource;
 
+#[cfg(feature = "icon_png")]
 use std::env::var;
-use std::fs::OpenOptions;
-use std::io::Write;
 #[cfg(feature = "icon_png")]
 use std::process::Command;
 use std::sync::atomic::{AtomicU16, Ordering};
 use camino::Utf8Path;
+use crate::Artifact;
 
 const ICON_RESOURCE_SCRIPT: &str = "[ID] ICON \"[PATH]\"\n";
 const MAGICK_COMMAND_SCALE_PNG: &str = "convert [INPUT] -scale [SCALE]x[SCALE] -extent [SCALE]x[SCALE] -background None -alpha on [OUTPUT][SCALE].png";
@@ -29,38 +29,35 @@ const PLACEHOLDER: &[u8] = include_bytes!("../icon.ico");
 #[cfg(feature = "icon_placeholder")]
 /// add a todo icon to the executable
 pub fn placeholder() {
-    let output_dir = var("OUT_DIR").unwrap();
-    let png_path = format!("{output_dir}/icon.ico");
-    let _ = std::fs::File::options()
-        .write(true)
-        .create(true)
-        .open(&png_path)
-        .unwrap()
-        .write(PLACEHOLDER)
-        .unwrap();
-    icon_ico(png_path);
+    icon_ico(super::link::write_out_file("icon.ico", PLACEHOLDER));
 }
 
 #[cfg(feature = "icon_autodetect")]
 /// autodetect icon format based on file ending
 pub fn icon<P: AsRef<Utf8Path>>(path: P) {
+    icon_for(path, &Artifact::Bins);
+}
+
+#[cfg(feature = "icon_autodetect")]
+/// like `icon`, but links the icon only to the given artifact
+pub fn icon_for<P: AsRef<Utf8Path>>(path: P, artifact: &Artifact) {
     let path = path.as_ref();
     assert!(path.exists(), "File does not exist");
 
     if let Some(extension) = path.extension() {
         #[cfg(feature = "icon_ico")]
         if extension == "ico" {
-            icon_ico(path);
+            icon_ico_for(path, artifact);
             return;
         }
         #[cfg(feature = "icon_png")]
         if extension == "png" {
-            icon_png(path);
+            icon_png_for(path, artifact);
             return;
         }
     }
     #[cfg(feature = "icon_magick")]
-    icon_magick(path);
+    icon_magick_for(path, artifact);
     #[cfg(not(feature = "icon_magick"))]
     panic!(
         r#"Can not convert or embed the icon at "{:?}". You may be missing the `icon_xxx` feature"#,
@@ -71,39 +68,39 @@ pub fn icon<P: AsRef<Utf8Path>>(path: P) {
 #[cfg(feature = "icon_ico")]
 /// link icon in `ico` format to executable
 pub fn icon_ico<P: AsRef<Utf8Path>>(path: P) {
+    icon_ico_for(path, &Artifact::Bins);
+}
+
+#[cfg(feature = "icon_ico")]
+/// like `icon_ico`, but links the icon only to the given artifact
+pub fn icon_ico_for<P: AsRef<Utf8Path>>(path: P, artifact: &Artifact) {
     let path = path.as_ref();
     assert!(path.exists(), "Path does not exist");
 
-    let output_dir = var("OUT_DIR").unwrap();
-    let build_res_file = format!("{output_dir}/icon{}.rc", CURRENT_ICON_ID.load(Ordering::Relaxed));
-
-    let mut file = OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .open(&build_res_file)
-        .unwrap();
-    let resource_script_content = ICON_RESOURCE_SCRIPT
-        .replace(
-            "[PATH]",
-            &path.as_str().replace('\\', "/"),
-        )
-        .replace("[ID]", &format!("icon{}", CURRENT_ICON_ID.load(Ordering::Relaxed)));
-    
-    CURRENT_ICON_ID.fetch_add(1, Ordering::Relaxed);
-
-    assert_eq!(
-        resource_script_content.len(),
-        file.write(resource_script_content.as_bytes()).unwrap(),
-        "An error occurred while writing the resource file."
-    );
+    let id = CURRENT_ICON_ID.fetch_add(1, Ordering::Relaxed);
+    // the icon is referenced relative to the script to keep absolute paths out of it
+    let icon = super::link::copy_asset(path, &format!("icon{id}.ico"));
+    let build_res_file = super::link::write_script(&format!("icon{id}.rc"), &icon_script(id, &icon));
 
-    super::link::link(build_res_file);
+    super::link::link_for(build_res_file, artifact);
+}
+
+#[cfg(feature = "icon_ico")]
+pub(crate) fn icon_script(id: u16, icon: &str) -> String {
+    ICON_RESOURCE_SCRIPT
+        .replace("[PATH]", icon)
+        .replace("[ID]", &format!("icon{id}"))
 }
 
 #[cfg(feature = "icon_png")]
 /// convert and scale `png` format to `ico` using imagemagick
 pub fn icon_png<P: AsRef<Utf8Path>>(path: P) {
+    icon_png_for(path, &Artifact::Bins);
+}
+
+#[cfg(feature = "icon_png")]
+/// like `icon_png`, but links the icon only to the given artifact
+pub fn icon_png_for<P: AsRef<Utf8Path>>(path: P, artifact: &Artifact) {
     let path = path.as_ref();
     assert!(path.exists(), "Path does not exist");
 
@@ -146,12 +143,18 @@ pub fn icon_png<P: AsRef<Utf8Path>>(path: P) {
         .expect("Execution failed")
         .success());
 
-    icon_ico(icon_path);
+    icon_ico_for(icon_path, artifact);
 }
 
 #[cfg(feature = "icon_magick")]
 /// convert any format to `png` using imagemagick and link it
 pub fn icon_magick<P: AsRef<Utf8Path>>(path: P) {
+    icon_magick_for(path, &Artifact::Bins);
+}
+
+#[cfg(feature = "icon_magick")]
+/// like `icon_magick`, but links the icon only to the given artifact
+pub fn icon_magick_for<P: AsRef<Utf8Path>>(path: P, artifact: &Artifact) {
     let path = path.as_ref();
     assert!(path.exists(), "Path does not exist");
 
@@ -171,5 +174,5 @@ pub fn icon_magick<P: AsRef<Utf8Path>>(path: P) {
         .expect("Execution failed")
         .success());
 
-    icon_png(png_path);
+    icon_png_for(png_path, artifact);
 }
@@ -4,12 +4,41 @@
 #[cfg(feature = "icon_ico")]
 pub mod icon;
 mod link;
+pub use link::{set_script_encoding, Artifact, ScriptEncoding};
 #[cfg(feature = "manifest")]
-mod manifest;
+pub mod manifest;
 #[cfg(feature = "manifest")]
-pub use manifest::manifest;
+pub use manifest::{manifest, manifest_for};
+#[cfg(feature = "metadata")]
+pub mod metadata;
+#[cfg(feature = "metadata")]
+pub use metadata::from_manifest;
+#[cfg(feature = "raw")]
+pub mod raw;
+#[cfg(feature = "string_table")]
+pub mod string_table;
 #[cfg(feature = "versioninfo")]
 pub mod versioninfo;
 
+/// the strict, reserved and weak keywords of Rust, which can not be used as names of constants
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+    "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self",
+    "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while", "abstract",
+    "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+    "macro_rules", "raw", "safe", "union",
+];
+
+/// whether `name` can be used as name of a generated Rust constant
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first == '_' || first.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+        && name != "_"
+        && !RUST_KEYWORDS.contains(&name)
+}
+
 #[cfg(test)]
 mod test;
@@ -1,35 +1,233 @@
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::sync::Mutex;
 #[cfg(feature = "build_cfg")]
-const WINDRES_COMMAND: &str = "-i [INPUT] -O coff -F [ARCH] -o [OUTPUT] -v";
+const WINDRES_COMMAND: &str = "-i [INPUT] -I [INCLUDE] -O coff -F [ARCH] -o [OUTPUT] -v";
 #[cfg(not(feature = "build_cfg"))]
-const WINDRES_COMMAND: &str = "-i [INPUT] -O coff -o [OUTPUT] -v";
+const WINDRES_COMMAND: &str = "-i [INPUT] -I [INCLUDE] -O coff -o [OUTPUT] -v";
 #[cfg(all(feature = "build_cfg", not(test), not(feature = "embed_resource")))]
 use build_cfg::build_cfg;
-#[cfg(all(feature = "build_cfg", test))]
+#[cfg(all(feature = "build_cfg", test, not(feature = "embed_resource")))]
 use std::cfg as build_cfg;
 #[cfg(not(feature = "embed_resource"))]
 use std::process::Command;
 
+/// The artifacts of a package resources can be linked to
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Artifact {
+    /// every artifact: binaries, cdylibs, examples, tests and benchmarks.
+    /// Only used if chosen explicitly, the functions without an `Artifact` link to `Bins`
+    All,
+    /// all binaries
+    Bins,
+    /// the binary with the given name
+    Bin(String),
+    /// the cdylib
+    Cdylib,
+    /// all examples
+    Examples,
+    /// all tests
+    Tests,
+    /// all benchmarks
+    Benches,
+}
+
+impl Artifact {
+    /// the binary with the given name
+    pub fn bin<S: Into<String>>(name: S) -> Self {
+        Self::Bin(name.into())
+    }
+
+    /// whether resources linked to both artifacts end up in the same executable
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::All, _) | (_, Self::All) => true,
+            (Self::Bins, Self::Bin(_)) | (Self::Bin(_), Self::Bins) => true,
+            _ => self == other,
+        }
+    }
+
+    /// appended to the names of generated files to keep them apart per artifact
+    pub(crate) fn file_suffix(&self) -> String {
+        match self {
+            Self::All => String::new(),
+            Self::Bins => "-bins".to_string(),
+            Self::Bin(bin) => format!("-bin-{bin}"),
+            Self::Cdylib => "-cdylib".to_string(),
+            Self::Examples => "-examples".to_string(),
+            Self::Tests => "-tests".to_string(),
+            Self::Benches => "-benches".to_string(),
+        }
+    }
+
+    /// the cargo instruction passing `arg` to the linker of the artifact
+    pub(crate) fn link_arg(&self, arg: &str) -> String {
+        match self {
+            Self::All => format!("cargo:rustc-link-arg={arg}"),
+            Self::Bins => format!("cargo:rustc-link-arg-bins={arg}"),
+            Self::Bin(bin) => format!("cargo:rustc-link-arg-bin={bin}={arg}"),
+            Self::Cdylib => format!("cargo:rustc-link-arg-cdylib={arg}"),
+            Self::Examples => format!("cargo:rustc-link-arg-examples={arg}"),
+            Self::Tests => format!("cargo:rustc-link-arg-tests={arg}"),
+            Self::Benches => format!("cargo:rustc-link-arg-benches={arg}"),
+        }
+    }
+}
+
+/// How generated resource scripts are encoded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScriptEncoding {
+    /// chosen by the resource compiler `embed_resource` uses for the target:
+    /// `Utf16Le` for `rc.exe`, which compiles msvc targets on Windows hosts, `Utf8` for `windres` and `llvm-rc`.
+    /// The `versioninfo_force_utf8` and `versioninfo_force_utf16` features override the detection
+    #[default]
+    Auto,
+    /// UTF-8 without byte order mark.
+    /// Version information string tables are encoded in the codepage of their `CharacterSet`
+    Utf8,
+    /// UTF-16 little endian with byte order mark
+    Utf16Le,
+}
+
+pub(crate) static SCRIPT_ENCODING: Mutex<ScriptEncoding> = Mutex::new(ScriptEncoding::Auto);
+
+impl ScriptEncoding {
+    /// the encoding `Auto` stands for, other encodings are returned unchanged
+    pub fn resolve(self) -> Self {
+        self.resolve_with(std::env::var("CARGO_CFG_TARGET_ENV").ok().as_deref())
+    }
+
+    /// like `resolve`, but with the value of `CARGO_CFG_TARGET_ENV`
+    pub(crate) fn resolve_with(self, target_env: Option<&str>) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        match (cfg!(feature = "versioninfo_force_utf8"), cfg!(feature = "versioninfo_force_utf16")) {
+            (true, false) => Self::Utf8,
+            (false, true) => Self::Utf16Le,
+            // rc.exe compiles msvc targets on Windows hosts,
+            // windres and llvm-rc compile gnu targets and cross compiled msvc targets and read utf8
+            _ if cfg!(all(feature = "embed_resource", windows)) && target_env == Some("msvc") => Self::Utf16Le,
+            _ => Self::Utf8,
+        }
+    }
+
+    /// encodes a resource script
+    pub(crate) fn encode(self, script: &str) -> Vec<u8> {
+        match self.resolve() {
+            Self::Utf16Le => {
+                let mut encoded = vec![0xFF, 0xFE]; // UTF16LE-BOM
+                for utf16 in script.encode_utf16() {
+                    encoded.extend_from_slice(&utf16.to_le_bytes());
+                }
+                encoded
+            }
+            _ => script.as_bytes().to_vec(),
+        }
+    }
+}
+
+/// Sets the encoding of all resource scripts generated afterwards
+pub fn set_script_encoding(encoding: ScriptEncoding) {
+    *SCRIPT_ENCODING.lock().unwrap() = encoding;
+}
+
+/// the encoding set by `set_script_encoding`, resolved for the current backend
+pub(crate) fn script_encoding() -> ScriptEncoding {
+    SCRIPT_ENCODING.lock().unwrap().resolve()
+}
+
+/// Writes a resource script into `OUT_DIR` using the selected `ScriptEncoding`
+pub(crate) fn write_script(name: &str, script: &str) -> Utf8PathBuf {
+    write_out_file(name, &script_encoding().encode(script))
+}
+
+/// the `OUT_DIR` of the build script
+pub(crate) fn out_dir() -> Utf8PathBuf {
+    Utf8PathBuf::from(std::env::var("OUT_DIR").unwrap())
+}
+
+/// Writes a generated file into `OUT_DIR` and returns its path.
+/// The file is only rewritten if its content changed, so repeated builds leave it untouched
+pub(crate) fn write_out_file(name: &str, content: &[u8]) -> Utf8PathBuf {
+    write_file_in(&out_dir(), name, content)
+}
+
+/// like `write_out_file`, but into `dir`
+pub(crate) fn write_file_in(dir: &Utf8Path, name: &str, content: &[u8]) -> Utf8PathBuf {
+    let path = dir.join(name);
+    if std::fs::read(&path).map_or(true, |existing| existing != content) {
+        std::fs::write(&path, content).expect("An error occurred while writing the resource file.");
+    }
+    path
+}
+
+/// Copies a file referenced by a resource script next to the script in `OUT_DIR`,
+/// so the script refers to it by `name` instead of by an absolute path
+pub(crate) fn copy_asset(path: &Utf8Path, name: &str) -> String {
+    copy_asset_into(&out_dir(), path, name).unwrap_or_else(|error| panic!("Can not read {path}: {error}"))
+}
+
+/// like `copy_asset`, but into `dir` and failing if the file can not be read
+pub(crate) fn copy_asset_into(dir: &Utf8Path, path: &Utf8Path, name: &str) -> std::io::Result<String> {
+    let content = std::fs::read(path)?;
+    write_file_in(dir, name, &content);
+    Ok(name.to_owned())
+}
+
 pub fn link<P: AsRef<Utf8Path>>(resource_path: P) {
-    let resource_path = resource_path.as_ref();
+    link_for(resource_path, &Artifact::Bins);
+}
+
+/// whether resources are linked, with the `windows_only` feature only when targeting windows
+fn links_resources() -> bool {
     #[cfg(feature = "windows_only")]
     if let Err(error) = std::env::var("CARGO_CFG_WINDOWS") {
         // quit if variable does not exist as we are not targeting windows
         if error == std::env::VarError::NotPresent {
-            return;
+            return false;
         }
 
         panic!("Unexpected error {error} while checking for windows target");
     }
+    true
+}
+
+/// passes the arguments to the linker of the given artifact
+pub(crate) fn link_args_for(args: &[String], artifact: &Artifact) {
+    if !links_resources() {
+        return;
+    }
+    for arg in args {
+        println!("{}", artifact.link_arg(arg));
+    }
+}
+
+/// compiles and links the resource script only to the given artifact
+pub(crate) fn link_for<P: AsRef<Utf8Path>>(resource_path: P, artifact: &Artifact) {
+    let resource_path = resource_path.as_ref();
+    if !links_resources() {
+        return;
+    }
     
     #[cfg(feature = "embed_resource")]
-    embed_resource::compile(resource_path, embed_resource::NONE).manifest_optional().unwrap();
+    match artifact {
+        Artifact::All => embed_resource::compile_for_everything(resource_path, embed_resource::NONE),
+        Artifact::Bins => embed_resource::compile(resource_path, embed_resource::NONE),
+        Artifact::Bin(bin) => embed_resource::compile_for(resource_path, [bin], embed_resource::NONE),
+        Artifact::Cdylib => embed_resource::compile_for_cdylib(resource_path, embed_resource::NONE),
+        Artifact::Examples => embed_resource::compile_for_examples(resource_path, embed_resource::NONE),
+        Artifact::Tests => embed_resource::compile_for_tests(resource_path, embed_resource::NONE),
+        Artifact::Benches => embed_resource::compile_for_benchmarks(resource_path, embed_resource::NONE),
+    }
+    .manifest_optional()
+    .unwrap();
 
     #[cfg(all(not(feature = "embed_resource"), feature = "build_cfg"))]
     {
         let resource_file = format!("{resource_path}.a");
         let args = WINDRES_COMMAND
             .replace("[INPUT]", resource_path.as_str())
+            .replace("[INCLUDE]", resource_path.parent().map_or(".", Utf8Path::as_str))
             .replace("[OUTPUT]", &resource_file);
 
         #[cfg(feature = "build_cfg")]
@@ -60,6 +258,6 @@ pub fn link<P: AsRef<Utf8Path>>(resource_path: P) {
             .expect("Execution failed")
             .success());
 
-        println!("cargo:rustc-link-arg={resource_file}"); // Tell it to link
+        println!("{}", artifact.link_arg(&resource_file)); // Tell it to link
     }
 }
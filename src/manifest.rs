@@ -1,41 +1,272 @@
-use std::env::var;
-use std::fs::OpenOptions;
-use std::io::Write;
+//! Functions and structs for embedding application manifests into the executable
 use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Mutex;
 use camino::Utf8Path;
+use crate::Artifact;
+
+mod builder;
+mod com;
+mod merge;
+mod template;
+mod validate;
+pub use builder::{
+    ActiveCodePage, AssemblyIdentity, DpiAwareness, ExecutionLevel, HeapType, Manifest, ProcessorArchitecture,
+    SupportedOS,
+};
+pub use com::{AssemblyFile, ComClass, ComInterface, ThreadingModel, TypeLib, TYPELIB_MARSHALER};
+pub use merge::MergedManifest;
+pub use template::{expand_template, rerun_if_template_changed};
+use template::package_version;
+pub use validate::{validate, ManifestError, ManifestErrorKind};
 
 const MANIFEST_RESOURCE_SCRIPT: &str = "#define RT_MANIFEST 24
 [ID] RT_MANIFEST \"[PATH]\"\n";
-pub(crate) static CURRENT_MANIFEST_ID: AtomicU16 = AtomicU16::new(0);
+const ASM_V1: &str = "urn:schemas-microsoft-com:asm.v1";
+const ASM_V2: &str = "urn:schemas-microsoft-com:asm.v2";
+const ASM_V3: &str = "urn:schemas-microsoft-com:asm.v3";
+const COMPATIBILITY_V1: &str = "urn:schemas-microsoft-com:compatibility.v1";
+const WINRT_V1: &str = "urn:schemas-microsoft-com:winrt.v1";
+const MSIX_V1: &str = "urn:schemas-microsoft-com:msix.v1";
+const WINDOWS_SETTINGS_2005: &str = "http://schemas.microsoft.com/SMI/2005/WindowsSettings";
+const WINDOWS_SETTINGS_2011: &str = "http://schemas.microsoft.com/SMI/2011/WindowsSettings";
+const WINDOWS_SETTINGS_2013: &str = "http://schemas.microsoft.com/SMI/2013/WindowsSettings";
+const WINDOWS_SETTINGS_2016: &str = "http://schemas.microsoft.com/SMI/2016/WindowsSettings";
+const WINDOWS_SETTINGS_2017: &str = "http://schemas.microsoft.com/SMI/2017/WindowsSettings";
+const WINDOWS_SETTINGS_2019: &str = "http://schemas.microsoft.com/SMI/2019/WindowsSettings";
+const WINDOWS_SETTINGS_2020: &str = "http://schemas.microsoft.com/SMI/2020/WindowsSettings";
+const WINDOWS_SETTINGS_2024: &str = "http://schemas.microsoft.com/SMI/2024/WindowsSettings";
+
+/// number of the next generated manifest file
+pub(crate) static CURRENT_MANIFEST_FILE: AtomicU16 = AtomicU16::new(0);
+/// the artifacts, resource ids and resolved strategies manifests have been linked with
+pub(crate) static LINKED_MANIFESTS: Mutex<Vec<(Artifact, u16, ManifestStrategy)>> = Mutex::new(Vec::new());
+
+/// the strategy set by `set_manifest_strategy`
+pub(crate) static MANIFEST_STRATEGY: Mutex<ManifestStrategy> = Mutex::new(ManifestStrategy::Auto);
+
+/// How manifests are embedded into the artifacts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ManifestStrategy {
+    /// `Linker` for the binaries of `msvc` targets, `Resource` otherwise
+    #[default]
+    Auto,
+    /// compiles a resource script with an `RT_MANIFEST` resource
+    Resource,
+    /// passes the manifest to the MSVC linker using `/MANIFEST:EMBED` and `/MANIFESTINPUT`,
+    /// which merges it with the manifest the linker generates instead of failing on a duplicate resource.
+    /// `/MANIFESTUAC:NO` is added if the manifest requests an execution level itself.
+    /// Only supported by `link.exe` and `lld-link`, and only one manifest can be passed per artifact
+    Linker,
+}
+
+impl ManifestStrategy {
+    /// the strategy `Auto` stands for when linking to `artifact`, other strategies are returned unchanged
+    pub fn resolve_for(self, artifact: &Artifact) -> Self {
+        self.resolve_with(std::env::var("CARGO_CFG_TARGET_ENV").ok().as_deref(), artifact)
+    }
+
+    /// like `resolve_for`, but with the value of `CARGO_CFG_TARGET_ENV`
+    pub(crate) fn resolve_with(self, target_env: Option<&str>, artifact: &Artifact) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        // the linker arguments of the other artifacts also reach artifacts linked separately
+        match (target_env, artifact) {
+            (Some("msvc"), Artifact::Bins | Artifact::Bin(_)) => Self::Linker,
+            _ => Self::Resource,
+        }
+    }
+}
+
+/// Sets how manifests linked afterwards are embedded
+pub fn set_manifest_strategy(strategy: ManifestStrategy) {
+    *MANIFEST_STRATEGY.lock().unwrap() = strategy;
+}
 
-/// adds an application manifest to an executable
-pub fn manifest<P: AsRef<Utf8Path>>(path: P) {
+/// resource id of the manifest read when an executable is started
+pub const CREATEPROCESS_MANIFEST_RESOURCE_ID: u16 = 1;
+/// resource id of the manifest read when a DLL is loaded
+pub const ISOLATIONAWARE_MANIFEST_RESOURCE_ID: u16 = 2;
+
+/// The resource id Windows reads the manifest of the artifact from:
+/// `ISOLATIONAWARE_MANIFEST_RESOURCE_ID` for a cdylib and `CREATEPROCESS_MANIFEST_RESOURCE_ID` otherwise.
+/// Manifests linked to `Artifact::All` are only honoured for executables
+pub const fn manifest_id(artifact: &Artifact) -> u16 {
+    match artifact {
+        Artifact::Cdylib => ISOLATIONAWARE_MANIFEST_RESOURCE_ID,
+        _ => CREATEPROCESS_MANIFEST_RESOURCE_ID,
+    }
+}
+
+/// adds an application manifest to the binaries.
+/// Fails if a manifest was already linked
+pub fn manifest<P: AsRef<Utf8Path>>(path: P) -> Result<(), &'static str> {
+    manifest_for(path, &Artifact::Bins)
+}
+
+/// like `manifest`, but links the manifest only to the given artifact using the resource id
+/// returned by `manifest_id`
+pub fn manifest_for<P: AsRef<Utf8Path>>(path: P, artifact: &Artifact) -> Result<(), &'static str> {
+    manifest_with_id(path, artifact, manifest_id(artifact))
+}
+
+/// like `manifest_for`, but with an explicit resource id.
+/// Every artifact can only have one manifest per resource id.
+/// The manifest is checked by `validate` first, problems are shown as build script warnings
+/// and prevent linking
+pub fn manifest_with_id<P: AsRef<Utf8Path>>(path: P, artifact: &Artifact, id: u16) -> Result<(), &'static str> {
     let path = path.as_ref();
-    assert!(path.exists(), "Path does not exist");
-
-    let output_dir = var("OUT_DIR").unwrap();
-    let build_res_file = format!("{output_dir}/manifest{}.rc", CURRENT_MANIFEST_ID.load(Ordering::Relaxed));
-
-    let mut file = OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .open(&build_res_file)
-        .unwrap();
-    let resource_script_content = MANIFEST_RESOURCE_SCRIPT
-        .replace(
-            "[PATH]",
-            &path.as_str().replace('\\', "/"),
+    let bytes = std::fs::read(path).map_err(|error| {
+        println!("cargo:warning=manifest {path}: {error}");
+        "The manifest can not be read, see the build script warnings"
+    })?;
+    let xml = decode(&bytes);
+    check(path.as_str(), &xml)?;
+    let strategy = MANIFEST_STRATEGY.lock().unwrap().resolve_for(artifact);
+    reserve(artifact, id, strategy)?;
+
+    let file = CURRENT_MANIFEST_FILE.fetch_add(1, Ordering::Relaxed);
+    // the manifest is referenced relative to the script to keep absolute paths out of it
+    let manifest = format!("manifest{file}.manifest");
+    super::link::write_out_file(&manifest, &bytes);
+    link_manifest(file, id, &manifest, &xml, artifact, strategy);
+    Ok(())
+}
+
+/// Adds an application manifest given as UTF-8 or as UTF-16 with byte order mark to the binaries.
+/// `${VAR}` placeholders are replaced, see `expand_template`.
+/// Fails if a manifest was already linked
+pub fn manifest_content<C: AsRef<[u8]>>(content: C) -> Result<(), &'static str> {
+    manifest_content_for(content, &Artifact::Bins)
+}
+
+/// like `manifest_content`, but links the manifest only to the given artifact using the resource id
+/// returned by `manifest_id`
+pub fn manifest_content_for<C: AsRef<[u8]>>(content: C, artifact: &Artifact) -> Result<(), &'static str> {
+    manifest_content_with_id(content, artifact, manifest_id(artifact))
+}
+
+/// like `manifest_content_for`, but with an explicit resource id
+pub fn manifest_content_with_id<C: AsRef<[u8]>>(content: C, artifact: &Artifact, id: u16) -> Result<(), &'static str> {
+    let xml = expand_template(&decode(content.as_ref())).map_err(|error| {
+        println!("cargo:warning=manifest template: {error}");
+        "Invalid manifest template, see the build script warnings"
+    })?;
+    link_xml(&xml, artifact, id)
+}
+
+/// decodes a manifest saved as UTF-8 or as UTF-16 with byte order mark
+fn decode(bytes: &[u8]) -> String {
+    let utf16 = |rest: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        String::from_utf16_lossy(
+            &rest
+                .chunks_exact(2)
+                .map(|pair| from_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>(),
         )
-        .replace("[ID]", &format!("manifest{}", CURRENT_MANIFEST_ID.load(Ordering::Relaxed)));
-    
-    CURRENT_MANIFEST_ID.fetch_add(1, Ordering::Relaxed);
+    };
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        bytes => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
 
-    assert_eq!(
-        resource_script_content.len(),
-        file.write(resource_script_content.as_bytes()).unwrap(),
-        "An error occurred while writing the resource file."
-    );
+/// validates the manifest and shows the problems as build script warnings,
+/// fails unless all of them are warnings
+fn check(source: &str, xml: &str) -> Result<(), &'static str> {
+    let errors = validate(xml);
+    for error in &errors {
+        println!("cargo:warning={source}: {error}");
+    }
+    if errors.iter().all(|error| error.kind.is_warning()) {
+        Ok(())
+    } else {
+        Err("Invalid manifest, see the build script warnings")
+    }
+}
+
+/// records the resource id as used by the artifact,
+/// fails if an overlapping artifact already has a manifest with this id.
+/// The linker embeds only one manifest per artifact, so with the resolved strategy `Linker`
+/// it also fails if an overlapping artifact already has a manifest passed to the linker
+pub(crate) fn reserve(artifact: &Artifact, id: u16, strategy: ManifestStrategy) -> Result<(), &'static str> {
+    let mut linked = LINKED_MANIFESTS.lock().unwrap();
+    if linked
+        .iter()
+        .any(|(other, other_id, _)| *other_id == id && other.overlaps(artifact))
+    {
+        return Err("Only one manifest can be linked per artifact");
+    }
+    if strategy == ManifestStrategy::Linker
+        && linked.iter().any(|(other, _, other_strategy)| {
+            *other_strategy == ManifestStrategy::Linker && other.overlaps(artifact)
+        })
+    {
+        return Err("Only one manifest can be passed to the linker per artifact");
+    }
+    linked.push((artifact.clone(), id, strategy));
+    Ok(())
+}
+
+/// validates a generated manifest, writes it into `OUT_DIR` and links it
+fn link_xml(xml: &str, artifact: &Artifact, id: u16) -> Result<(), &'static str> {
+    check("generated manifest", xml)?;
+    let strategy = MANIFEST_STRATEGY.lock().unwrap().resolve_for(artifact);
+    reserve(artifact, id, strategy)?;
+    let file = CURRENT_MANIFEST_FILE.fetch_add(1, Ordering::Relaxed);
+    let manifest = format!("manifest{file}.manifest");
+    super::link::write_out_file(&manifest, xml.as_bytes());
+    link_manifest(file, id, &manifest, xml, artifact, strategy);
+    Ok(())
+}
+
+/// escapes the characters with a special meaning in XML text and attribute values
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// links the manifest file `manifest` in `OUT_DIR` with the content `xml` using the resolved strategy
+fn link_manifest(file: u16, id: u16, manifest: &str, xml: &str, artifact: &Artifact, strategy: ManifestStrategy) {
+    if strategy == ManifestStrategy::Linker {
+        let path = Utf8Path::new(&std::env::var("OUT_DIR").unwrap()).join(manifest);
+        super::link::link_args_for(&manifest_linker_args(id, path.as_str(), xml), artifact);
+        return;
+    }
+    let build_res_file =
+        super::link::write_script(&format!("manifest{file}.rc"), &manifest_script(id, manifest));
+
+    super::link::link_for(build_res_file, artifact);
+}
+
+/// the arguments making the MSVC linker embed the manifest at `path` with the resource id `id`
+pub(crate) fn manifest_linker_args(id: u16, path: &str, xml: &str) -> Vec<String> {
+    let mut args = vec![format!("/MANIFEST:EMBED,ID={id}"), format!("/MANIFESTINPUT:{path}")];
+    // the execution level the linker adds by default conflicts with the one of the manifest
+    let requests_level = roxmltree::Document::parse(xml).is_ok_and(|document| {
+        document
+            .descendants()
+            .any(|node| node.tag_name().name() == "requestedExecutionLevel")
+    });
+    if requests_level {
+        args.push("/MANIFESTUAC:NO".to_owned());
+    }
+    args
+}
 
-    super::link::link(build_res_file);
+pub(crate) fn manifest_script(id: u16, manifest: &str) -> String {
+    MANIFEST_RESOURCE_SCRIPT
+        .replace("[PATH]", manifest)
+        .replace("[ID]", &id.to_string())
 }
@@ -0,0 +1,442 @@
+//! Application manifests generated from typed settings instead of a handwritten XML file
+use super::*;
+use std::env::var;
+use std::fmt::Write as FmtWrite;
+
+/// The privileges the executable requests when it is started
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionLevel {
+    /// the privileges of the parent process
+    AsInvoker,
+    /// the highest privileges the current user can obtain
+    HighestAvailable,
+    /// administrator privileges, the user is prompted if necessary
+    RequireAdministrator,
+}
+
+impl ExecutionLevel {
+    /// the value of the `level` attribute
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::AsInvoker => "asInvoker",
+            Self::HighestAvailable => "highestAvailable",
+            Self::RequireAdministrator => "requireAdministrator",
+        }
+    }
+}
+
+/// The DPI awareness of the process, written as `dpiAware` for older systems
+/// and as `dpiAwareness` for Windows 10 version 1607 and later
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DpiAwareness {
+    /// the system scales the window
+    Unaware,
+    /// the window is scaled for the DPI of the primary monitor
+    System,
+    /// the window handles DPI changes itself
+    PerMonitor,
+    /// like `PerMonitor`, with automatic scaling of the non-client area and common controls.
+    /// Falls back to `PerMonitor` before Windows 10 version 1703
+    PerMonitorV2,
+}
+
+impl DpiAwareness {
+    /// the value of the `dpiAware` element
+    pub const fn dpi_aware(&self) -> &str {
+        match self {
+            Self::Unaware => "false",
+            Self::System => "true",
+            Self::PerMonitor | Self::PerMonitorV2 => "true/pm",
+        }
+    }
+
+    /// the value of the `dpiAwareness` element
+    pub const fn dpi_awareness(&self) -> &str {
+        match self {
+            Self::Unaware => "unaware",
+            Self::System => "system",
+            Self::PerMonitor => "permonitor",
+            Self::PerMonitorV2 => "permonitorv2,permonitor",
+        }
+    }
+}
+
+/// The codepage used by the `A` variants of the Windows API
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActiveCodePage {
+    /// UTF-8, supported since Windows 10 version 1903
+    Utf8,
+    /// the codepage of the system locale, also if UTF-8 is configured system wide
+    Legacy,
+}
+
+impl ActiveCodePage {
+    /// the value of the `activeCodePage` element
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Legacy => "Legacy",
+        }
+    }
+}
+
+/// The heap implementation used by the process
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeapType {
+    /// the segment heap, supported since Windows 10 version 2004
+    SegmentHeap,
+}
+
+impl HeapType {
+    /// the value of the `heapType` element
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::SegmentHeap => "SegmentHeap",
+        }
+    }
+}
+
+/// A Windows version the executable was tested on, see
+/// [the compatibility section](https://learn.microsoft.com/en-us/windows/win32/sysinfo/targeting-your-application-at-windows-8-1)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SupportedOS {
+    Windows7,
+    Windows8,
+    Windows81,
+    /// Windows 10 and Windows 11 share one id
+    Windows10,
+}
+
+impl SupportedOS {
+    /// every variant, in declaration order
+    pub const ALL: [Self; 4] = [Self::Windows7, Self::Windows8, Self::Windows81, Self::Windows10];
+
+    /// the value of the `Id` attribute
+    pub const fn guid(&self) -> &str {
+        match self {
+            Self::Windows7 => "{35138b9a-5d96-4fbd-8e2d-a2440225f93a}",
+            Self::Windows8 => "{4a2f28e3-53b9-4441-ba9c-d69d4a4a6e38}",
+            Self::Windows81 => "{1f676c76-80e1-4239-95bb-83d0f6d0da78}",
+            Self::Windows10 => "{8e0f7a12-bfb3-4fe8-b9a5-48fd50a15a9a}",
+        }
+    }
+}
+
+/// The processor architecture of an assembly
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProcessorArchitecture {
+    X86,
+    Amd64,
+    Arm,
+    Arm64,
+}
+
+impl ProcessorArchitecture {
+    /// the value of the `processorArchitecture` attribute
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::X86 => "x86",
+            Self::Amd64 => "amd64",
+            Self::Arm => "arm",
+            Self::Arm64 => "arm64",
+        }
+    }
+
+    /// the architecture of the compilation target, `None` for targets Windows does not run on
+    pub fn from_target() -> Option<Self> {
+        match var("CARGO_CFG_TARGET_ARCH").ok()?.as_str() {
+            "x86" => Some(Self::X86),
+            "x86_64" => Some(Self::Amd64),
+            "arm" => Some(Self::Arm),
+            "aarch64" => Some(Self::Arm64),
+            _ => None,
+        }
+    }
+}
+
+/// The identity of the executable or of an assembly it depends on, written as `assemblyIdentity`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssemblyIdentity {
+    /// e.g. `Company.Product.Name`
+    pub name: String,
+    pub version: [u16; 4],
+    /// omitted if `None`
+    pub processor_architecture: Option<ProcessorArchitecture>,
+}
+
+impl AssemblyIdentity {
+    /// the package name and version with the architecture of the compilation target.
+    /// The fourth version component is the numeric pre-release version or 0
+    pub fn from_cargo_env() -> Self {
+        Self {
+            name: var("CARGO_PKG_NAME").unwrap_or_default(),
+            version: package_version(),
+            processor_architecture: ProcessorArchitecture::from_target(),
+        }
+    }
+
+    /// the internal name of the first string table and the file version
+    /// with the architecture of the compilation target.
+    /// The package name is used if there are no string tables
+    #[cfg(feature = "versioninfo")]
+    pub fn from_version_info(version_info: &crate::versioninfo::VersionInfo) -> Self {
+        let version = version_info.file_version;
+        Self {
+            name: version_info
+                .file_info
+                .first()
+                .map(|file_info| file_info.internal_name.as_str().to_owned())
+                .unwrap_or_else(|| var("CARGO_PKG_NAME").unwrap_or_default()),
+            version: [version.0, version.1, version.2, version.3],
+            processor_architecture: ProcessorArchitecture::from_target(),
+        }
+    }
+
+    fn write(&self, result: &mut String, indent: &str) -> std::fmt::Result {
+        let [major, minor, patch, build] = self.version;
+        write!(
+            result,
+            r#"{indent}<assemblyIdentity type="win32" name="{}" version="{major}.{minor}.{patch}.{build}""#,
+            escape_xml(&self.name)
+        )?;
+        if let Some(architecture) = self.processor_architecture {
+            write!(result, r#" processorArchitecture="{}""#, architecture.as_str())?;
+        }
+        writeln!(result, "/>")
+    }
+}
+
+/// The settings of an application manifest.
+/// Implements `Display` writing the manifest XML, unset settings are omitted.
+/// ```
+/// use windows_exe_info::manifest::*;
+/// let manifest = Manifest::new()
+///     .with_execution_level(ExecutionLevel::AsInvoker)
+///     .with_dpi_awareness(DpiAwareness::PerMonitorV2)
+///     .with_common_controls();
+/// assert!(manifest.to_string().contains(r#"level="asInvoker""#));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// `assemblyIdentity`, required for side-by-side dependencies of the executable
+    pub assembly_identity: Option<AssemblyIdentity>,
+    /// `requestedExecutionLevel`
+    pub execution_level: Option<ExecutionLevel>,
+    /// `uiAccess` of the `requestedExecutionLevel`, allows controlling windows of elevated processes.
+    /// Requires a signed executable in a secure location
+    pub ui_access: bool,
+    pub dpi_awareness: Option<DpiAwareness>,
+    /// `longPathAware`, paths longer than `MAX_PATH` if enabled in the registry
+    pub long_path_aware: Option<bool>,
+    pub active_code_page: Option<ActiveCodePage>,
+    pub heap_type: Option<HeapType>,
+    /// `supportedOS` entries of the compatibility section
+    pub supported_os: Vec<SupportedOS>,
+    /// adds a dependency on version 6 of the common controls, enabling visual styles
+    pub common_controls: bool,
+    /// private assemblies in the directory of the executable, written as `dependentAssembly`
+    pub dependencies: Vec<AssemblyIdentity>,
+    /// files of the assembly, e.g. DLLs implementing COM classes activated without registry entries
+    pub files: Vec<AssemblyFile>,
+    /// `comInterfaceExternalProxyStub` entries
+    pub com_interfaces: Vec<ComInterface>,
+}
+
+impl Manifest {
+    /// a manifest without any settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a manifest with the identity `AssemblyIdentity::from_cargo_env`
+    pub fn from_cargo_env() -> Self {
+        Self::new().with_assembly_identity(AssemblyIdentity::from_cargo_env())
+    }
+
+    /// a manifest with the identity `AssemblyIdentity::from_version_info`
+    #[cfg(feature = "versioninfo")]
+    pub fn from_version_info(version_info: &crate::versioninfo::VersionInfo) -> Self {
+        Self::new().with_assembly_identity(AssemblyIdentity::from_version_info(version_info))
+    }
+
+    pub fn with_assembly_identity(mut self, assembly_identity: AssemblyIdentity) -> Self {
+        self.assembly_identity = Some(assembly_identity);
+        self
+    }
+
+    pub fn with_execution_level(mut self, execution_level: ExecutionLevel) -> Self {
+        self.execution_level = Some(execution_level);
+        self
+    }
+
+    /// sets `uiAccess`, the execution level defaults to `AsInvoker`
+    pub fn with_ui_access(mut self, ui_access: bool) -> Self {
+        self.execution_level.get_or_insert(ExecutionLevel::AsInvoker);
+        self.ui_access = ui_access;
+        self
+    }
+
+    pub fn with_dpi_awareness(mut self, dpi_awareness: DpiAwareness) -> Self {
+        self.dpi_awareness = Some(dpi_awareness);
+        self
+    }
+
+    pub fn with_long_path_aware(mut self, long_path_aware: bool) -> Self {
+        self.long_path_aware = Some(long_path_aware);
+        self
+    }
+
+    pub fn with_active_code_page(mut self, active_code_page: ActiveCodePage) -> Self {
+        self.active_code_page = Some(active_code_page);
+        self
+    }
+
+    pub fn with_heap_type(mut self, heap_type: HeapType) -> Self {
+        self.heap_type = Some(heap_type);
+        self
+    }
+
+    /// adds the given systems to the compatibility section, e.g. `&SupportedOS::ALL`
+    pub fn with_supported_os(mut self, supported_os: &[SupportedOS]) -> Self {
+        for os in supported_os {
+            if !self.supported_os.contains(os) {
+                self.supported_os.push(*os);
+            }
+        }
+        self.supported_os.sort();
+        self
+    }
+
+    pub fn with_common_controls(mut self) -> Self {
+        self.common_controls = true;
+        self
+    }
+
+    /// adds a dependency on a private assembly, its manifest has to declare the same identity
+    pub fn with_dependency(mut self, dependency: AssemblyIdentity) -> Self {
+        self.dependencies.push(dependency);
+        self
+    }
+
+    pub fn with_file(mut self, file: AssemblyFile) -> Self {
+        self.files.push(file);
+        self
+    }
+
+    pub fn with_com_interface(mut self, com_interface: ComInterface) -> Self {
+        self.com_interfaces.push(com_interface);
+        self
+    }
+
+    /// Writes the manifest into `OUT_DIR` and links it to the binaries.
+    /// Fails if a manifest was already linked
+    pub fn link(&self) -> Result<(), &str> {
+        self.link_for(&Artifact::Bins)
+    }
+
+    /// like `link`, but links the manifest only to the given artifact using the resource id
+    /// returned by `manifest_id`
+    pub fn link_for(&self, artifact: &Artifact) -> Result<(), &str> {
+        self.link_with_id(artifact, manifest_id(artifact))
+    }
+
+    /// like `link_for`, but with an explicit resource id
+    pub fn link_with_id(&self, artifact: &Artifact, id: u16) -> Result<(), &str> {
+        link_xml(&self.to_string(), artifact, id)
+    }
+
+    fn has_windows_settings(&self) -> bool {
+        self.dpi_awareness.is_some()
+            || self.long_path_aware.is_some()
+            || self.active_code_page.is_some()
+            || self.heap_type.is_some()
+    }
+}
+
+impl core::fmt::Display for Manifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut result = String::new();
+        writeln!(result, r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#)?;
+        writeln!(result, r#"<assembly xmlns="{ASM_V1}" manifestVersion="1.0">"#)?;
+
+        if let Some(identity) = &self.assembly_identity {
+            identity.write(&mut result, "  ")?;
+        }
+
+        if let Some(level) = self.execution_level {
+            writeln!(result, r#"  <trustInfo xmlns="{ASM_V3}">"#)?;
+            writeln!(result, "    <security>")?;
+            writeln!(result, "      <requestedPrivileges>")?;
+            writeln!(
+                result,
+                r#"        <requestedExecutionLevel level="{}" uiAccess="{}"/>"#,
+                level.as_str(),
+                self.ui_access
+            )?;
+            writeln!(result, "      </requestedPrivileges>")?;
+            writeln!(result, "    </security>")?;
+            writeln!(result, "  </trustInfo>")?;
+        }
+
+        if !self.supported_os.is_empty() {
+            writeln!(result, r#"  <compatibility xmlns="{COMPATIBILITY_V1}">"#)?;
+            writeln!(result, "    <application>")?;
+            for os in &self.supported_os {
+                writeln!(result, r#"      <supportedOS Id="{}"/>"#, os.guid())?;
+            }
+            writeln!(result, "    </application>")?;
+            writeln!(result, "  </compatibility>")?;
+        }
+
+        if self.has_windows_settings() {
+            let setting = |result: &mut String, name: &str, namespace: &str, value: &str| {
+                writeln!(result, r#"      <{name} xmlns="{namespace}">{value}</{name}>"#)
+            };
+            writeln!(result, r#"  <application xmlns="{ASM_V3}">"#)?;
+            writeln!(result, "    <windowsSettings>")?;
+            if let Some(dpi_awareness) = self.dpi_awareness {
+                setting(&mut result, "dpiAware", WINDOWS_SETTINGS_2005, dpi_awareness.dpi_aware())?;
+                setting(&mut result, "dpiAwareness", WINDOWS_SETTINGS_2016, dpi_awareness.dpi_awareness())?;
+            }
+            if let Some(long_path_aware) = self.long_path_aware {
+                setting(&mut result, "longPathAware", WINDOWS_SETTINGS_2016, &long_path_aware.to_string())?;
+            }
+            if let Some(active_code_page) = self.active_code_page {
+                setting(&mut result, "activeCodePage", WINDOWS_SETTINGS_2019, active_code_page.as_str())?;
+            }
+            if let Some(heap_type) = self.heap_type {
+                setting(&mut result, "heapType", WINDOWS_SETTINGS_2020, heap_type.as_str())?;
+            }
+            writeln!(result, "    </windowsSettings>")?;
+            writeln!(result, "  </application>")?;
+        }
+
+        if self.common_controls {
+            writeln!(result, "  <dependency>")?;
+            writeln!(result, "    <dependentAssembly>")?;
+            writeln!(
+                result,
+                r#"      <assemblyIdentity type="win32" name="Microsoft.Windows.Common-Controls" version="6.0.0.0" processorArchitecture="*" publicKeyToken="6595b64144ccf1df" language="*"/>"#
+            )?;
+            writeln!(result, "    </dependentAssembly>")?;
+            writeln!(result, "  </dependency>")?;
+        }
+
+        for dependency in &self.dependencies {
+            writeln!(result, "  <dependency>")?;
+            writeln!(result, "    <dependentAssembly>")?;
+            dependency.write(&mut result, "      ")?;
+            writeln!(result, "    </dependentAssembly>")?;
+            writeln!(result, "  </dependency>")?;
+        }
+        for file in &self.files {
+            file.write(&mut result)?;
+        }
+        for com_interface in &self.com_interfaces {
+            com_interface.write(&mut result)?;
+        }
+
+        writeln!(result, "</assembly>")?;
+        write!(f, "{result}")
+    }
+}
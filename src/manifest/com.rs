@@ -0,0 +1,167 @@
+//! Declarations for registration-free COM and private side-by-side assemblies
+use super::*;
+use std::fmt::Write as FmtWrite;
+
+/// `proxyStubClsid32` of interfaces marshaled using their type library
+pub const TYPELIB_MARSHALER: &str = "{00020424-0000-0000-C000-000000000046}";
+
+/// The threading model of a COM class
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThreadingModel {
+    Apartment,
+    Free,
+    Both,
+    Neutral,
+}
+
+impl ThreadingModel {
+    /// the value of the `threadingModel` attribute
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::Apartment => "Apartment",
+            Self::Free => "Free",
+            Self::Both => "Both",
+            Self::Neutral => "Neutral",
+        }
+    }
+}
+
+/// A COM class implemented by an [`AssemblyFile`], written as `comClass`.
+/// The ids are GUIDs in braces, e.g. `{35138b9a-5d96-4fbd-8e2d-a2440225f93a}`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComClass {
+    pub clsid: String,
+    pub threading_model: Option<ThreadingModel>,
+    pub progid: Option<String>,
+    /// the type library describing the class
+    pub tlbid: Option<String>,
+    pub description: Option<String>,
+}
+
+impl ComClass {
+    /// a class without threading model, progid, type library and description
+    pub fn new(clsid: &str) -> Self {
+        Self {
+            clsid: clsid.to_owned(),
+            threading_model: None,
+            progid: None,
+            tlbid: None,
+            description: None,
+        }
+    }
+}
+
+/// A type library contained in an [`AssemblyFile`], written as `typelib`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeLib {
+    pub tlbid: String,
+    /// e.g. `1.0`
+    pub version: String,
+    /// directory of the help files relative to the file, usually empty
+    pub help_dir: String,
+}
+
+/// An interface used across apartments, written as `comInterfaceExternalProxyStub`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComInterface {
+    pub iid: String,
+    pub name: String,
+    /// the type library describing the interface
+    pub tlbid: Option<String>,
+    /// the class of the proxy, e.g. `TYPELIB_MARSHALER`
+    pub proxy_stub_clsid32: Option<String>,
+}
+
+/// A file of the assembly, written as `file`, e.g. a DLL next to the executable implementing COM classes.
+/// A DLL describing itself as side-by-side assembly embeds its manifest with the resource id 1,
+/// see `Manifest::link_with_id`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssemblyFile {
+    /// the file name relative to the manifest
+    pub name: String,
+    pub com_classes: Vec<ComClass>,
+    pub typelibs: Vec<TypeLib>,
+}
+
+impl AssemblyFile {
+    /// a file without COM classes and type libraries
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_com_class(mut self, com_class: ComClass) -> Self {
+        self.com_classes.push(com_class);
+        self
+    }
+
+    pub fn with_typelib(mut self, typelib: TypeLib) -> Self {
+        self.typelibs.push(typelib);
+        self
+    }
+}
+
+/// writes the optional attributes with a value
+fn attributes(result: &mut String, attributes: &[(&str, Option<&str>)]) -> std::fmt::Result {
+    for (name, value) in attributes {
+        if let Some(value) = value {
+            write!(result, r#" {name}="{}""#, escape_xml(value))?;
+        }
+    }
+    Ok(())
+}
+
+impl AssemblyFile {
+    pub(super) fn write(&self, result: &mut String) -> std::fmt::Result {
+        write!(result, r#"  <file name="{}""#, escape_xml(&self.name))?;
+        if self.com_classes.is_empty() && self.typelibs.is_empty() {
+            return writeln!(result, "/>");
+        }
+        writeln!(result, ">")?;
+        for class in &self.com_classes {
+            write!(result, "    <comClass")?;
+            attributes(
+                result,
+                &[
+                    ("clsid", Some(&class.clsid)),
+                    ("threadingModel", class.threading_model.as_ref().map(ThreadingModel::as_str)),
+                    ("progid", class.progid.as_deref()),
+                    ("tlbid", class.tlbid.as_deref()),
+                    ("description", class.description.as_deref()),
+                ],
+            )?;
+            writeln!(result, "/>")?;
+        }
+        for typelib in &self.typelibs {
+            write!(result, "    <typelib")?;
+            attributes(
+                result,
+                &[
+                    ("tlbid", Some(&typelib.tlbid)),
+                    ("version", Some(&typelib.version)),
+                    ("helpdir", Some(&typelib.help_dir)),
+                ],
+            )?;
+            writeln!(result, "/>")?;
+        }
+        writeln!(result, "  </file>")
+    }
+}
+
+impl ComInterface {
+    pub(super) fn write(&self, result: &mut String) -> std::fmt::Result {
+        write!(result, "  <comInterfaceExternalProxyStub")?;
+        attributes(
+            result,
+            &[
+                ("iid", Some(&self.iid)),
+                ("name", Some(&self.name)),
+                ("tlbid", self.tlbid.as_deref()),
+                ("proxyStubClsid32", self.proxy_stub_clsid32.as_deref()),
+            ],
+        )?;
+        writeln!(result, "/>")
+    }
+}
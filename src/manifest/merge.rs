@@ -0,0 +1,235 @@
+//! Combination of several manifests into the single one Windows reads
+use super::*;
+use std::fmt::Write as FmtWrite;
+use super::validate::{is_same_element, is_single};
+use roxmltree::{Document, Node};
+
+/// an element of a parsed manifest
+#[derive(Clone, Debug)]
+struct XmlElement {
+    namespace: String,
+    name: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    children: Vec<XmlElement>,
+    /// line of the element in the manifest it was read from
+    line: usize,
+}
+
+/// equal elements are not added twice, regardless of where they were read from
+impl PartialEq for XmlElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.namespace == other.namespace
+            && self.name == other.name
+            && self.attributes == other.attributes
+            && self.text == other.text
+            && self.children == other.children
+    }
+}
+
+impl Eq for XmlElement {}
+
+impl XmlElement {
+    fn read(document: &Document, node: Node) -> Self {
+        Self {
+            namespace: node.tag_name().namespace().unwrap_or_default().to_owned(),
+            name: node.tag_name().name().to_owned(),
+            attributes: node
+                .attributes()
+                .map(|attribute| (attribute.name().to_owned(), attribute.value().to_owned()))
+                .collect(),
+            text: node
+                .children()
+                .filter(Node::is_text)
+                .filter_map(|text| text.text())
+                .collect::<String>()
+                .trim()
+                .to_owned(),
+            children: node
+                .children()
+                .filter(Node::is_element)
+                .map(|child| Self::read(document, child))
+                .collect(),
+            line: document.text_pos_at(node.range().start).row as usize,
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// whether `other` is the same element and has to be merged into this one
+    fn matches(&self, other: &Self) -> bool {
+        if self.name != other.name || !is_same_element(&self.name, &self.namespace, &other.namespace) {
+            return false;
+        }
+        if self.name == "supportedOS" {
+            return self.attribute("Id") == other.attribute("Id");
+        }
+        is_single(&self.namespace, &self.name) || self == other
+    }
+
+    /// adds the attributes, text and children of `other`, failing on different values
+    fn merge(&mut self, other: &Self) -> Result<(), ManifestError> {
+        let conflict = |attribute: Option<&str>, first: &str, second: &str| ManifestError {
+            line: other.line,
+            kind: ManifestErrorKind::Conflict {
+                element: other.name.clone(),
+                attribute: attribute.map(str::to_owned),
+                first: first.to_owned(),
+                second: second.to_owned(),
+            },
+        };
+        for (name, value) in &other.attributes {
+            match self.attribute(name) {
+                Some(existing) if existing != value => return Err(conflict(Some(name), existing, value)),
+                Some(_) => {}
+                None => self.attributes.push((name.clone(), value.clone())),
+            }
+        }
+        match (self.text.as_str(), other.text.as_str()) {
+            (_, "") => {}
+            ("", text) => self.text = text.to_owned(),
+            (existing, text) if existing != text => return Err(conflict(None, existing, text)),
+            _ => {}
+        }
+        for child in &other.children {
+            match self.children.iter_mut().find(|existing| existing.matches(child)) {
+                Some(existing) => existing.merge(child)?,
+                // the identity of the assembly has to be its first element
+                None if child.name == "assemblyIdentity" => self.children.insert(0, child.clone()),
+                None => self.children.push(child.clone()),
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&self, result: &mut String, parent_namespace: &str, depth: usize) -> std::fmt::Result {
+        let indent = "  ".repeat(depth);
+        write!(result, "{indent}<{}", self.name)?;
+        if self.namespace != parent_namespace {
+            write!(result, r#" xmlns="{}""#, escape_xml(&self.namespace))?;
+        }
+        for (name, value) in &self.attributes {
+            write!(result, r#" {name}="{}""#, escape_xml(value))?;
+        }
+        match (self.text.is_empty(), self.children.is_empty()) {
+            (true, true) => writeln!(result, "/>"),
+            (false, true) => writeln!(result, ">{}</{}>", escape_xml(&self.text), self.name),
+            _ => {
+                writeln!(result, ">")?;
+                if !self.text.is_empty() {
+                    writeln!(result, "{indent}  {}", escape_xml(&self.text))?;
+                }
+                for child in &self.children {
+                    child.write(result, &self.namespace, depth + 1)?;
+                }
+                writeln!(result, "{indent}</{}>", self.name)
+            }
+        }
+    }
+}
+
+/// Several manifests combined into one, e.g. settings contributed by different crates.
+/// Elements that may appear only once are merged, different values of their attributes or text
+/// are reported as `ManifestErrorKind::Conflict`. Other elements are added unless an equal one exists.
+/// Implements `Display` writing the merged manifest XML.
+/// ```
+/// use windows_exe_info::manifest::*;
+/// let merged = MergedManifest::new()
+///     .with_manifest(&Manifest::new().with_dpi_awareness(DpiAwareness::PerMonitorV2))
+///     .unwrap()
+///     .with_manifest(&Manifest::new().with_common_controls())
+///     .unwrap();
+/// // conflicting DPI awareness
+/// assert!(merged
+///     .clone()
+///     .with_manifest(&Manifest::new().with_dpi_awareness(DpiAwareness::System))
+///     .is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergedManifest {
+    root: XmlElement,
+}
+
+impl Default for MergedManifest {
+    fn default() -> Self {
+        Self {
+            root: XmlElement {
+                namespace: ASM_V1.to_owned(),
+                name: "assembly".to_owned(),
+                attributes: vec![("manifestVersion".to_owned(), "1.0".to_owned())],
+                text: String::new(),
+                children: Vec::new(),
+                line: 1,
+            },
+        }
+    }
+}
+
+impl MergedManifest {
+    /// a manifest without any settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a complete manifest, i.e. one with an `assembly` root element.
+    /// Fails if the manifest is invalid (see `validate`, warnings are ignored)
+    /// or conflicts with the manifests added before, the line numbers refer to `xml`
+    pub fn with_xml(mut self, xml: &str) -> Result<Self, Vec<ManifestError>> {
+        let errors = validate(xml);
+        if !errors.iter().all(|error| error.kind.is_warning()) {
+            return Err(errors);
+        }
+        let document = Document::parse(xml).expect("the manifest was validated");
+        let fragment = XmlElement::read(&document, document.root_element());
+        self.root.merge(&fragment).map_err(|conflict| vec![conflict])?;
+        Ok(self)
+    }
+
+    /// like `with_xml`, but reads the manifest from a file saved as UTF-8 or as UTF-16 with byte order mark
+    pub fn with_file<P: AsRef<Utf8Path>>(self, path: P) -> Result<Self, Vec<ManifestError>> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|error| {
+            vec![ManifestError {
+                line: 0,
+                kind: ManifestErrorKind::Xml(format!("could not read {path}: {error}")),
+            }]
+        })?;
+        self.with_xml(&decode(&bytes))
+    }
+
+    /// like `with_xml`, but adds the manifest generated by `manifest`
+    pub fn with_manifest(self, manifest: &Manifest) -> Result<Self, Vec<ManifestError>> {
+        self.with_xml(&manifest.to_string())
+    }
+
+    /// Writes the merged manifest into `OUT_DIR` and links it to the binaries.
+    /// Fails if a manifest was already linked
+    pub fn link(&self) -> Result<(), &str> {
+        self.link_for(&Artifact::Bins)
+    }
+
+    /// like `link`, but links the manifest only to the given artifact using the resource id
+    /// returned by `manifest_id`
+    pub fn link_for(&self, artifact: &Artifact) -> Result<(), &str> {
+        self.link_with_id(artifact, manifest_id(artifact))
+    }
+
+    /// like `link_for`, but with an explicit resource id
+    pub fn link_with_id(&self, artifact: &Artifact, id: u16) -> Result<(), &str> {
+        link_xml(&self.to_string(), artifact, id)
+    }
+}
+
+impl core::fmt::Display for MergedManifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut result = String::new();
+        writeln!(result, r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#)?;
+        self.root.write(&mut result, "", 0)?;
+        write!(f, "{result}")
+    }
+}
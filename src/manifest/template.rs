@@ -0,0 +1,65 @@
+//! `${VAR}` placeholders in manifests, replaced by environment variables set by cargo
+use super::*;
+use std::env::var;
+
+/// Replaces every `${VAR}` in `template` by the value of the environment variable `VAR`,
+/// escaped for XML, e.g. `${CARGO_PKG_NAME}`.
+/// `${ASSEMBLY_VERSION}` is the package version with the four components `assemblyIdentity` requires,
+/// the fourth is the numeric pre-release version or 0.
+/// Fails if a variable is not set.
+/// No `cargo:rerun-if-env-changed` directives are printed, see `rerun_if_template_changed`
+pub fn expand_template(template: &str) -> Result<String, String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let Some(length) = rest[start..].find('}') else {
+            return Err(format!("unclosed placeholder `{}`", &rest[start..]));
+        };
+        let name = &rest[start + 2..start + length];
+        let value = match name {
+            "ASSEMBLY_VERSION" => {
+                let [major, minor, patch, pre] = package_version();
+                format!("{major}.{minor}.{patch}.{pre}")
+            }
+            name => var(name).map_err(|_| format!("the environment variable `{name}` of `${{{name}}}` is not set"))?,
+        };
+        expanded.push_str(&escape_xml(&value));
+        rest = &rest[start + length + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Tells cargo to rerun the build script when one of the environment variables used by `template` changes.
+/// The first `rerun-if` directive stops cargo from rerunning the build script on every change of the package,
+/// so the other inputs of the build script have to be declared as well
+pub fn rerun_if_template_changed(template: &str) {
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else {
+            return;
+        };
+        let name = &rest[start + 2..start + length];
+        if name != "ASSEMBLY_VERSION" {
+            println!("cargo:rerun-if-env-changed={name}");
+        }
+        rest = &rest[start + length + 1..];
+    }
+}
+
+/// the components `MAJOR.MINOR.PATCH.PRE` of the package version
+pub(crate) fn package_version() -> [u16; 4] {
+    let component = |name: &str| -> u16 {
+        var(name)
+            .ok()
+            .and_then(|component| component.parse().ok())
+            .unwrap_or_default()
+    };
+    [
+        component("CARGO_PKG_VERSION_MAJOR"),
+        component("CARGO_PKG_VERSION_MINOR"),
+        component("CARGO_PKG_VERSION_PATCH"),
+        component("CARGO_PKG_VERSION_PRE"),
+    ]
+}
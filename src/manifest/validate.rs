@@ -0,0 +1,501 @@
+//! Checks run on manifest XML before embedding it
+use super::*;
+use roxmltree::{Document, Node};
+
+/// A problem found by [`validate`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestError {
+    /// line of the manifest the problem was found in, starting at 1. 0 if the problem is not related to a line
+    pub line: usize,
+    pub kind: ManifestErrorKind,
+}
+
+/// The kind of a [`ManifestError`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ManifestErrorKind {
+    /// the manifest is not well-formed XML
+    Xml(String),
+    /// the root element is not an `assembly` element in the `urn:schemas-microsoft-com:asm.v1` namespace
+    InvalidRoot { element: String, namespace: String },
+    /// an element missing from the manifest schemas known to this crate.
+    /// A warning in the Microsoft namespaces, since newer Windows versions add elements to them, see `is_warning`
+    UnknownElement { element: String, namespace: String },
+    /// a known element in the wrong namespace, e.g. `dpiAware` without the `WindowsSettings` namespace
+    WrongNamespace {
+        element: String,
+        namespace: String,
+        expected: &'static str,
+    },
+    /// a known element inside an element it does not belong to
+    UnexpectedElement { element: String, parent: String },
+    /// an element that is allowed only once appears again
+    DuplicateElement { element: String },
+    /// a required attribute is missing
+    MissingAttribute { element: String, attribute: &'static str },
+    /// the text of an element or the value of an attribute is not allowed
+    InvalidValue {
+        element: String,
+        attribute: Option<&'static str>,
+        value: String,
+    },
+    /// merged manifests contain different values for the text or an attribute of the same element
+    Conflict {
+        element: String,
+        attribute: Option<String>,
+        first: String,
+        second: String,
+    },
+}
+
+impl ManifestErrorKind {
+    /// whether the manifest can be linked anyway,
+    /// true for unknown elements in the namespaces of Microsoft
+    pub fn is_warning(&self) -> bool {
+        match self {
+            Self::UnknownElement { namespace, .. } => {
+                namespace.starts_with("urn:schemas-microsoft-com:") || namespace.starts_with("http://schemas.microsoft.com/")
+            }
+            _ => false,
+        }
+    }
+}
+
+impl core::fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ManifestErrorKind::Xml(message) => write!(f, "{message}"),
+            ManifestErrorKind::InvalidRoot { element, namespace } => write!(
+                f,
+                "the root element must be `assembly` in the `{ASM_V1}` namespace, not `{element}` in `{namespace}`"
+            ),
+            ManifestErrorKind::UnknownElement { element, namespace } => {
+                write!(f, "unknown element `{element}` in the namespace `{namespace}`")
+            }
+            ManifestErrorKind::WrongNamespace {
+                element,
+                namespace,
+                expected,
+            } => write!(
+                f,
+                "`{element}` must be in the namespace `{expected}`, not `{namespace}`"
+            ),
+            ManifestErrorKind::UnexpectedElement { element, parent } => {
+                write!(f, "`{element}` is not allowed inside `{parent}`")
+            }
+            ManifestErrorKind::DuplicateElement { element } => write!(f, "`{element}` is only allowed once"),
+            ManifestErrorKind::MissingAttribute { element, attribute } => {
+                write!(f, "`{element}` has no `{attribute}` attribute")
+            }
+            ManifestErrorKind::InvalidValue {
+                element,
+                attribute: Some(attribute),
+                value,
+            } => write!(f, "invalid value \"{value}\" of `{element}` attribute `{attribute}`"),
+            ManifestErrorKind::InvalidValue {
+                element,
+                attribute: None,
+                value,
+            } => write!(f, "invalid value \"{value}\" of `{element}`"),
+            ManifestErrorKind::Conflict {
+                element,
+                attribute: Some(attribute),
+                first,
+                second,
+            } => write!(
+                f,
+                "`{element}` attribute `{attribute}` is \"{first}\" in one manifest and \"{second}\" in another"
+            ),
+            ManifestErrorKind::Conflict {
+                element,
+                attribute: None,
+                first,
+                second,
+            } => write!(f, "`{element}` is \"{first}\" in one manifest and \"{second}\" in another"),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+/// checks the text of an element or the value of an attribute
+type Check = fn(&str) -> bool;
+
+/// the name and the namespaces of an element
+type Name = (&'static str, &'static [&'static str]);
+
+/// the schema of a manifest element
+struct Element {
+    name: &'static str,
+    namespaces: &'static [&'static str],
+    parents: &'static [Name],
+    /// whether the element may appear only once per parent
+    single: bool,
+    /// check of the text content
+    text: Option<Check>,
+    /// name, whether it is required and the check of the value
+    attributes: &'static [(&'static str, bool, Option<Check>)],
+}
+
+const TRUST_INFO: &[&str] = &[ASM_V2, ASM_V3];
+const ASSEMBLY: &[Name] = &[("assembly", &[ASM_V1])];
+const WINDOWS_SETTINGS: &[Name] = &[("windowsSettings", &[ASM_V3])];
+
+const fn element(name: &'static str, namespaces: &'static [&'static str], parents: &'static [Name]) -> Element {
+    Element {
+        name,
+        namespaces,
+        parents,
+        single: false,
+        text: None,
+        attributes: &[],
+    }
+}
+
+const fn single(name: &'static str, namespaces: &'static [&'static str], parents: &'static [Name]) -> Element {
+    Element {
+        single: true,
+        ..element(name, namespaces, parents)
+    }
+}
+
+/// a setting in the `windowsSettings` element
+const fn setting(name: &'static str, namespace: &'static [&'static str], text: Check) -> Element {
+    Element {
+        text: Some(text),
+        ..single(name, namespace, WINDOWS_SETTINGS)
+    }
+}
+
+const ELEMENTS: &[Element] = &[
+    Element {
+        attributes: &[("manifestVersion", true, Some(|value| value == "1.0"))],
+        ..single("assembly", &[ASM_V1], &[])
+    },
+    Element {
+        attributes: &[
+            ("name", true, None),
+            ("version", false, Some(is_version)),
+            ("type", false, Some(|value| matches!(value, "win32" | "win32-policy"))),
+            ("processorArchitecture", false, Some(is_architecture)),
+            ("publicKeyToken", false, Some(|value| value.len() == 16 && is_hex(value))),
+        ],
+        ..single("assemblyIdentity", &[ASM_V1], &[("assembly", &[ASM_V1]), ("dependentAssembly", &[ASM_V1])])
+    },
+    single("description", &[ASM_V1], ASSEMBLY),
+    single("noInherit", &[ASM_V1], ASSEMBLY),
+    single("noInheritable", &[ASM_V1], ASSEMBLY),
+    element("dependency", &[ASM_V1], ASSEMBLY),
+    single("dependentAssembly", &[ASM_V1], &[("dependency", &[ASM_V1])]),
+    Element {
+        attributes: &[("oldVersion", true, None), ("newVersion", true, Some(is_version))],
+        ..element("bindingRedirect", &[ASM_V1], &[("dependentAssembly", &[ASM_V1])])
+    },
+    Element {
+        attributes: &[("name", true, None)],
+        ..element("file", &[ASM_V1], ASSEMBLY)
+    },
+    Element {
+        attributes: &[
+            ("clsid", true, Some(is_guid)),
+            ("threadingModel", false, Some(is_threading_model)),
+            ("tlbid", false, Some(is_guid)),
+        ],
+        ..element("comClass", &[ASM_V1], &[("file", &[ASM_V1])])
+    },
+    element("progid", &[ASM_V1], &[("comClass", &[ASM_V1])]),
+    Element {
+        attributes: &[
+            ("tlbid", true, Some(is_guid)),
+            ("version", true, None),
+            ("helpdir", true, None),
+        ],
+        ..element("typelib", &[ASM_V1], &[("file", &[ASM_V1])])
+    },
+    Element {
+        attributes: &[
+            ("iid", true, Some(is_guid)),
+            ("proxyStubClsid32", false, Some(is_guid)),
+            ("tlbid", false, Some(is_guid)),
+        ],
+        ..element("comInterfaceExternalProxyStub", &[ASM_V1], ASSEMBLY)
+    },
+    Element {
+        attributes: &[
+            ("clsid", true, Some(is_guid)),
+            ("name", true, None),
+            ("threadingModel", false, Some(is_threading_model)),
+        ],
+        ..element("clrClass", &[ASM_V1], ASSEMBLY)
+    },
+    Element {
+        attributes: &[("name", true, None), ("threadingModel", true, None)],
+        ..element("activatableClass", &[WINRT_V1], &[("file", &[ASM_V1])])
+    },
+    Element {
+        attributes: &[("publisher", true, None), ("packageName", true, None), ("applicationId", true, None)],
+        ..single("msix", &[MSIX_V1], ASSEMBLY)
+    },
+    Element {
+        attributes: &[
+            ("iid", true, Some(is_guid)),
+            ("proxyStubClsid32", false, Some(is_guid)),
+            ("tlbid", false, Some(is_guid)),
+        ],
+        ..element("comInterfaceProxyStub", &[ASM_V1], &[("file", &[ASM_V1])])
+    },
+    element("windowClass", &[ASM_V1], &[("file", &[ASM_V1])]),
+    single("trustInfo", TRUST_INFO, ASSEMBLY),
+    single("security", TRUST_INFO, &[("trustInfo", TRUST_INFO)]),
+    single("requestedPrivileges", TRUST_INFO, &[("security", TRUST_INFO)]),
+    Element {
+        attributes: &[
+            (
+                "level",
+                true,
+                Some(|value| matches!(value, "asInvoker" | "highestAvailable" | "requireAdministrator")),
+            ),
+            ("uiAccess", false, Some(is_bool)),
+        ],
+        ..single("requestedExecutionLevel", TRUST_INFO, &[("requestedPrivileges", TRUST_INFO)])
+    },
+    single("compatibility", &[COMPATIBILITY_V1], ASSEMBLY),
+    single("application", &[COMPATIBILITY_V1], &[("compatibility", &[COMPATIBILITY_V1])]),
+    Element {
+        attributes: &[("Id", true, Some(is_supported_os))],
+        ..element("supportedOS", &[COMPATIBILITY_V1], &[("application", &[COMPATIBILITY_V1])])
+    },
+    Element {
+        attributes: &[("Id", true, Some(is_version))],
+        ..element("maxversiontested", &[COMPATIBILITY_V1], &[("application", &[COMPATIBILITY_V1])])
+    },
+    single("application", &[ASM_V3], ASSEMBLY),
+    single("windowsSettings", &[ASM_V3], &[("application", &[ASM_V3])]),
+    setting("autoElevate", &[WINDOWS_SETTINGS_2005], is_bool),
+    setting("disableTheming", &[WINDOWS_SETTINGS_2005], is_bool),
+    setting("dpiAware", &[WINDOWS_SETTINGS_2005], |value| {
+        ["true", "false", "true/pm", "per monitor"]
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(value))
+    }),
+    setting("disableWindowFiltering", &[WINDOWS_SETTINGS_2011], is_bool),
+    setting("printerDriverIsolation", &[WINDOWS_SETTINGS_2011], is_bool),
+    setting("highResolutionScrollingAware", &[WINDOWS_SETTINGS_2013], is_bool),
+    setting("ultraHighResolutionScrollingAware", &[WINDOWS_SETTINGS_2013], is_bool),
+    setting("dpiAwareness", &[WINDOWS_SETTINGS_2016], |value| {
+        value.split(',').all(|awareness| {
+            ["unaware", "system", "permonitor", "permonitorv2"]
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(awareness.trim()))
+        })
+    }),
+    setting("longPathAware", &[WINDOWS_SETTINGS_2016], is_bool),
+    setting("gdiScaling", &[WINDOWS_SETTINGS_2017], is_bool),
+    setting("activeCodePage", &[WINDOWS_SETTINGS_2019], |value| {
+        // a locale name is accepted since Windows 11
+        value == "UTF-8"
+            || value == "Legacy"
+            || value
+                .split('-')
+                .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()))
+    }),
+    setting("heapType", &[WINDOWS_SETTINGS_2020], |value| value == "SegmentHeap"),
+    setting("supportedArchitectures", &[WINDOWS_SETTINGS_2024], |value| {
+        value
+            .split_whitespace()
+            .all(|architecture| matches!(architecture, "amd64" | "arm64"))
+    }),
+];
+
+/// whether the element may appear only once per parent
+pub(super) fn is_single(namespace: &str, name: &str) -> bool {
+    ELEMENTS
+        .iter()
+        .any(|element| element.single && element.name == name && element.namespaces.contains(&namespace))
+}
+
+/// whether the elements named `name` in the two namespaces are the same element,
+/// e.g. `trustInfo` in `asm.v2` and `asm.v3`
+pub(super) fn is_same_element(name: &str, namespace: &str, other_namespace: &str) -> bool {
+    namespace == other_namespace
+        || ELEMENTS.iter().any(|element| {
+            element.name == name
+                && element.namespaces.contains(&namespace)
+                && element.namespaces.contains(&other_namespace)
+        })
+}
+
+fn is_bool(value: &str) -> bool {
+    value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false")
+}
+
+fn is_hex(value: &str) -> bool {
+    value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// four numbers separated by dots, e.g. `1.0.0.0`
+fn is_version(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('.').collect();
+    parts.len() == 4 && parts.iter().all(|part| part.parse::<u16>().is_ok())
+}
+
+fn is_architecture(value: &str) -> bool {
+    ["x86", "amd64", "arm64", "arm", "ia64", "msil", "*"]
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(value))
+}
+
+/// a GUID in braces, e.g. `{35138b9a-5d96-4fbd-8e2d-a2440225f93a}`
+fn is_guid(value: &str) -> bool {
+    let Some(guid) = value.strip_prefix('{').and_then(|value| value.strip_suffix('}')) else {
+        return false;
+    };
+    let groups: Vec<&str> = guid.split('-').collect();
+    groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12]) && groups.iter().all(|group| is_hex(group))
+}
+
+fn is_supported_os(value: &str) -> bool {
+    // Windows Vista is not covered by `SupportedOS`
+    value.eq_ignore_ascii_case("{e2011457-1546-43c5-a5fe-008deee3d3f0}")
+        || SupportedOS::ALL
+            .iter()
+            .any(|os| os.guid().eq_ignore_ascii_case(value))
+}
+
+fn is_threading_model(value: &str) -> bool {
+    ["Apartment", "Free", "Both", "Neutral"]
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(value))
+}
+
+/// Checks that `xml` is a well-formed manifest: the namespaces, element names, their position,
+/// the values of known settings and attributes and that single elements are not repeated.
+/// Unknown attributes are ignored, unknown elements in the Microsoft namespaces are warnings
+/// (see `ManifestErrorKind::is_warning`), which do not prevent linking.
+pub fn validate(xml: &str) -> Vec<ManifestError> {
+    let document = match Document::parse(xml) {
+        Ok(document) => document,
+        Err(error) => {
+            return vec![ManifestError {
+                line: error.pos().row as usize,
+                kind: ManifestErrorKind::Xml(error.to_string()),
+            }]
+        }
+    };
+    let mut errors = Vec::new();
+    let root = document.root_element();
+    if root.tag_name().name() != "assembly" || root.tag_name().namespace() != Some(ASM_V1) {
+        errors.push(ManifestError {
+            line: line(&document, root.range().start),
+            kind: ManifestErrorKind::InvalidRoot {
+                element: root.tag_name().name().to_owned(),
+                namespace: root.tag_name().namespace().unwrap_or_default().to_owned(),
+            },
+        });
+        return errors;
+    }
+    check_element(&document, root, &mut errors);
+    errors
+}
+
+fn line(document: &Document, position: usize) -> usize {
+    document.text_pos_at(position).row as usize
+}
+
+fn check_element(document: &Document, node: Node, errors: &mut Vec<ManifestError>) {
+    let name = node.tag_name().name();
+    let namespace = node.tag_name().namespace().unwrap_or_default();
+    let mut error = |kind| {
+        errors.push(ManifestError {
+            line: line(document, node.range().start),
+            kind,
+        })
+    };
+
+    let candidates: Vec<&Element> = ELEMENTS.iter().filter(|element| element.name == name).collect();
+    let Some(schema) = candidates
+        .iter()
+        .find(|element| element.namespaces.contains(&namespace))
+    else {
+        match candidates.first() {
+            Some(element) => error(ManifestErrorKind::WrongNamespace {
+                element: name.to_owned(),
+                namespace: namespace.to_owned(),
+                expected: element.namespaces[0],
+            }),
+            None => error(ManifestErrorKind::UnknownElement {
+                element: name.to_owned(),
+                namespace: namespace.to_owned(),
+            }),
+        }
+        // the content of unknown elements is not checked
+        return;
+    };
+
+    if let Some(parent) = node.parent_element() {
+        let (parent, parent_namespace) = (parent.tag_name().name(), parent.tag_name().namespace().unwrap_or_default());
+        // elements with the same name in other namespaces are different elements, e.g. `application`
+        if !schema
+            .parents
+            .iter()
+            .any(|(name, namespaces)| *name == parent && namespaces.contains(&parent_namespace))
+        {
+            error(ManifestErrorKind::UnexpectedElement {
+                element: name.to_owned(),
+                parent: parent.to_owned(),
+            });
+        }
+    }
+
+    // siblings are the same element if they have the same schema, e.g. `trustInfo` in `asm.v2` and `asm.v3`
+    let earlier = || {
+        node.prev_siblings().skip(1).filter(|sibling| {
+            sibling.tag_name().name() == name
+                && schema
+                    .namespaces
+                    .contains(&sibling.tag_name().namespace().unwrap_or_default())
+        })
+    };
+    if schema.single && earlier().next().is_some() {
+        error(ManifestErrorKind::DuplicateElement {
+            element: name.to_owned(),
+        });
+    }
+    if name == "supportedOS" {
+        let id = node.attribute("Id").unwrap_or_default();
+        if earlier().any(|sibling| sibling.attribute("Id").is_some_and(|other| other.eq_ignore_ascii_case(id))) {
+            error(ManifestErrorKind::DuplicateElement {
+                element: format!("supportedOS Id=\"{id}\""),
+            });
+        }
+    }
+
+    if let Some(check) = schema.text {
+        let text = node.text().unwrap_or_default().trim();
+        if !check(text) {
+            error(ManifestErrorKind::InvalidValue {
+                element: name.to_owned(),
+                attribute: None,
+                value: text.to_owned(),
+            });
+        }
+    }
+    for (attribute, required, check) in schema.attributes {
+        match (node.attribute(*attribute), check) {
+            (None, _) if *required => error(ManifestErrorKind::MissingAttribute {
+                element: name.to_owned(),
+                attribute,
+            }),
+            (Some(value), Some(check)) if !check(value) => error(ManifestErrorKind::InvalidValue {
+                element: name.to_owned(),
+                attribute: Some(attribute),
+                value: value.to_owned(),
+            }),
+            _ => {}
+        }
+    }
+
+    for child in node.children().filter(Node::is_element) {
+        check_element(document, child, errors);
+    }
+}
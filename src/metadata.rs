@@ -0,0 +1,320 @@
+//! Declarative configuration read from `[package.metadata.windows_exe_info]`.
+//!
+//! ```toml
+//! [package.metadata.windows_exe_info]
+//! icon = "assets/icon.ico"
+//! company-name = "Example Corp"
+//! legal-copyright = "Copyright (c) Example Corp"
+//! manifest = "assets/app.manifest"
+//! strings = { Homepage = "https://example.com" }
+//!
+//! # overrides for a single `[[bin]]` target
+//! [package.metadata.windows_exe_info.bin.example-cli]
+//! icon = "assets/cli.ico"
+//! ```
+//! Paths are specified relative to the root of the crate.
+use std::collections::BTreeMap;
+use std::env::var;
+use std::fmt::Display;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+
+use crate::Artifact;
+
+/// Error returned by [`from_manifest`]
+#[derive(Debug)]
+pub enum MetadataError {
+    /// `CARGO_MANIFEST_DIR` is not set, the function was not called from a build script
+    NoManifestDir,
+    /// reading `Cargo.toml` failed
+    Io(std::io::Error),
+    /// `Cargo.toml` or the metadata table could not be parsed. Also contains unknown keys
+    Toml(toml::de::Error),
+    /// a `bin` table names a binary the package does not have
+    UnknownBinary(String),
+    /// a key is not allowed at its position
+    UnknownKey(String),
+    /// a key was used that requires a crate feature which is not enabled
+    MissingFeature {
+        key: &'static str,
+        feature: &'static str,
+    },
+    /// linking the manifest or the version information failed
+    Link(String),
+}
+
+impl Display for MetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoManifestDir => write!(f, "CARGO_MANIFEST_DIR is not set"),
+            Self::Io(error) => write!(f, "could not read Cargo.toml: {error}"),
+            Self::Toml(error) => write!(f, "invalid [package.metadata.windows_exe_info]: {error}"),
+            Self::UnknownBinary(bin) => write!(
+                f,
+                "[package.metadata.windows_exe_info.bin.{bin}] does not match any binary of the package"
+            ),
+            Self::UnknownKey(key) => write!(
+                f,
+                "unknown key `{key}` in [package.metadata.windows_exe_info]"
+            ),
+            Self::MissingFeature { key, feature } => write!(
+                f,
+                "the `{key}` key requires the `{feature}` feature of windows_exe_info"
+            ),
+            Self::Link(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for MetadataError {}
+
+/// Settings for a single artifact.
+/// The package table additionally contains the per binary overrides.
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Metadata {
+    pub icon: Option<Utf8PathBuf>,
+    pub comment: Option<String>,
+    pub company_name: Option<String>,
+    pub legal_copyright: Option<String>,
+    pub legal_trademarks: Option<String>,
+    pub manifest: Option<Utf8PathBuf>,
+    #[serde(default)]
+    pub strings: BTreeMap<String, String>,
+    #[serde(default)]
+    pub bin: BTreeMap<String, Metadata>,
+}
+
+impl Metadata {
+    /// parses the metadata table out of the content of the `Cargo.toml` in `root`
+    /// and returns it together with the names of the binaries of the package, see `binaries`
+    pub(crate) fn parse(cargo_toml: &str, root: &Utf8Path) -> Result<(Self, Vec<String>), MetadataError> {
+        let manifest: CargoToml = toml::from_str(cargo_toml).map_err(MetadataError::Toml)?;
+        let bins = binaries(&manifest, root);
+        let metadata = manifest
+            .package
+            .metadata
+            .and_then(|metadata| metadata.windows_exe_info)
+            .unwrap_or_default();
+
+        if let Some((name, _)) = metadata.bin.iter().find(|(_, bin)| !bin.bin.is_empty()) {
+            return Err(MetadataError::UnknownKey(format!("bin.{name}.bin")));
+        }
+
+        Ok((metadata, bins))
+    }
+
+    /// combines the package settings with the overrides for one binary
+    fn overridden_by(&self, bin: &Metadata) -> Metadata {
+        let mut strings = self.strings.clone();
+        strings.extend(bin.strings.clone());
+        Metadata {
+            icon: bin.icon.clone().or_else(|| self.icon.clone()),
+            comment: bin.comment.clone().or_else(|| self.comment.clone()),
+            company_name: bin.company_name.clone().or_else(|| self.company_name.clone()),
+            legal_copyright: bin.legal_copyright.clone().or_else(|| self.legal_copyright.clone()),
+            legal_trademarks: bin.legal_trademarks.clone().or_else(|| self.legal_trademarks.clone()),
+            manifest: bin.manifest.clone().or_else(|| self.manifest.clone()),
+            strings,
+            bin: BTreeMap::new(),
+        }
+    }
+
+    /// links all configured resources to the given artifact
+    fn link(&self, root: &Utf8Path, artifact: &Artifact) -> Result<(), MetadataError> {
+        #[cfg(feature = "icon_ico")]
+        if let Some(icon) = &self.icon {
+            link_icon(&root.join(icon), artifact)?;
+        }
+        #[cfg(not(feature = "icon_ico"))]
+        if self.icon.is_some() {
+            return Err(MetadataError::MissingFeature {
+                key: "icon",
+                feature: "icon_ico",
+            });
+        }
+
+        #[cfg(feature = "manifest")]
+        if let Some(manifest) = &self.manifest {
+            crate::manifest::manifest_for(root.join(manifest), artifact)
+                .map_err(|error| MetadataError::Link(error.to_owned()))?;
+        }
+        #[cfg(not(feature = "manifest"))]
+        if self.manifest.is_some() {
+            return Err(MetadataError::MissingFeature {
+                key: "manifest",
+                feature: "manifest",
+            });
+        }
+
+        #[cfg(feature = "versioninfo")]
+        {
+            let mut version_info = crate::versioninfo::VersionInfo::from_cargo_env_ex(
+                self.comment.as_deref(),
+                self.company_name.as_deref(),
+                self.legal_copyright.as_deref(),
+                self.legal_trademarks.as_deref(),
+            )
+            .with_artifact(artifact);
+            for file_info in &mut version_info.file_info {
+                file_info.custom.extend(
+                    self.strings
+                        .iter()
+                        .map(|(key, value)| (key.clone(), value.as_str().into())),
+                );
+            }
+            version_info
+                .link_for(artifact)
+                .map_err(|error| MetadataError::Link(error.to_owned()))?;
+        }
+        #[cfg(not(feature = "versioninfo"))]
+        {
+            let version_keys = [
+                ("comment", self.comment.is_some()),
+                ("company-name", self.company_name.is_some()),
+                ("legal-copyright", self.legal_copyright.is_some()),
+                ("legal-trademarks", self.legal_trademarks.is_some()),
+                ("strings", !self.strings.is_empty()),
+            ];
+            if let Some((key, _)) = version_keys.into_iter().find(|(_, used)| *used) {
+                return Err(MetadataError::MissingFeature {
+                    key,
+                    feature: "versioninfo",
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "icon_ico")]
+fn link_icon(icon: &Utf8Path, artifact: &Artifact) -> Result<(), MetadataError> {
+    #[cfg(feature = "icon_autodetect")]
+    crate::icon::icon_for(icon, artifact);
+    #[cfg(not(feature = "icon_autodetect"))]
+    if icon.extension() == Some("ico") {
+        crate::icon::icon_ico_for(icon, artifact);
+    } else {
+        return Err(MetadataError::MissingFeature {
+            key: "icon",
+            feature: "icon_autodetect",
+        });
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct CargoToml {
+    package: Package,
+    #[serde(default)]
+    bin: Vec<Bin>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    autobins: Option<bool>,
+    metadata: Option<PackageMetadata>,
+}
+
+#[derive(Deserialize)]
+struct PackageMetadata {
+    windows_exe_info: Option<Metadata>,
+}
+
+#[derive(Deserialize)]
+struct Bin {
+    name: Option<String>,
+    path: Option<Utf8PathBuf>,
+}
+
+/// The names of the binaries of the package in `root`, found the way cargo does:
+/// the `[[bin]]` targets first, then unless `autobins = false` `src/main.rs`, `src/bin/NAME.rs`
+/// and `src/bin/NAME/main.rs` if no `[[bin]]` target has the same name or path
+fn binaries(manifest: &CargoToml, root: &Utf8Path) -> Vec<String> {
+    let package = &manifest.package.name;
+    let default_path = |name: &str| {
+        let file = Utf8PathBuf::from(format!("src/bin/{name}.rs"));
+        if name == package && root.join("src/main.rs").exists() {
+            Utf8PathBuf::from("src/main.rs")
+        } else if root.join(&file).exists() {
+            file
+        } else {
+            Utf8PathBuf::from(format!("src/bin/{name}/main.rs"))
+        }
+    };
+    let mut bins: Vec<(String, Utf8PathBuf)> = manifest
+        .bin
+        .iter()
+        .filter_map(|bin| {
+            let name = bin.name.clone()?;
+            let path = match &bin.path {
+                Some(path) => path.strip_prefix("./").unwrap_or(path).to_owned(),
+                None => default_path(&name),
+            };
+            Some((name, path))
+        })
+        .collect();
+
+    if manifest.package.autobins != Some(false) {
+        let mut discovered = Vec::new();
+        if root.join("src/main.rs").exists() {
+            discovered.push((package.clone(), Utf8PathBuf::from("src/main.rs")));
+        }
+        if let Ok(entries) = root.join("src/bin").read_dir_utf8() {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let (Some(name), Some(file_name)) = (path.file_stem(), path.file_name()) else {
+                    continue;
+                };
+                if path.extension() == Some("rs") {
+                    discovered.push((name.to_owned(), Utf8PathBuf::from(format!("src/bin/{file_name}"))));
+                } else if path.join("main.rs").exists() {
+                    discovered.push((file_name.to_owned(), Utf8PathBuf::from(format!("src/bin/{file_name}/main.rs"))));
+                }
+            }
+        }
+        for (name, path) in discovered {
+            if !bins.iter().any(|(other, other_path)| *other == name || *other_path == path) {
+                bins.push((name, path));
+            }
+        }
+    }
+
+    let mut names: Vec<String> = bins.into_iter().map(|(name, _)| name).collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Reads `[package.metadata.windows_exe_info]` from the `Cargo.toml` of the crate
+/// being built and links the configured icon, manifest and version information.
+///
+/// Tables in `[package.metadata.windows_exe_info.bin.NAME]` override the package
+/// settings for the binary `NAME`. If any such table exists, every binary of the
+/// package gets its own resources. The binaries are found the way cargo finds them,
+/// honouring `autobins = false` and the `path` of the `[[bin]]` targets.
+pub fn from_manifest() -> Result<(), MetadataError> {
+    let root = Utf8PathBuf::from(var("CARGO_MANIFEST_DIR").map_err(|_| MetadataError::NoManifestDir)?);
+    let cargo_toml = std::fs::read_to_string(root.join("Cargo.toml")).map_err(MetadataError::Io)?;
+    let (metadata, bins) = Metadata::parse(&cargo_toml, &root)?;
+
+    if metadata.bin.is_empty() {
+        return metadata.link(&root, &Artifact::Bins);
+    }
+
+    if let Some(unknown) = metadata.bin.keys().find(|name| !bins.contains(name)) {
+        return Err(MetadataError::UnknownBinary(unknown.clone()));
+    }
+
+    for bin in &bins {
+        let settings = match metadata.bin.get(bin) {
+            Some(overrides) => metadata.overridden_by(overrides),
+            None => metadata.overridden_by(&Metadata::default()),
+        };
+        settings.link(&root, &Artifact::bin(bin))?;
+    }
+    Ok(())
+}
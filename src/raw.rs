@@ -0,0 +1,397 @@
+//! Arbitrary files or bytes embedded as `RCDATA` or custom typed resources,
+//! loaded at runtime with `FindResourceW` and `LoadResource`
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Mutex;
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::versioninfo::Language;
+use crate::{Artifact, ScriptEncoding};
+
+/// number of the next generated resource file
+pub(crate) static CURRENT_RAW_FILE: AtomicU16 = AtomicU16::new(0);
+/// the artifacts, types, ids and languages raw resources have been linked to
+pub(crate) static LINKED_RAW_RESOURCES: Mutex<Vec<(Artifact, ResourceType, ResourceId, Language)>> =
+    Mutex::new(Vec::new());
+
+/// The type of a raw resource
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ResourceType {
+    /// `RT_RCDATA`
+    #[default]
+    RcData,
+    /// a numeric type, the values up to 255 are reserved for the predefined types
+    Numeric(u16),
+    /// a named type, e.g. `CONFIG`
+    Named(String),
+}
+
+/// The id of a resource, either a number or a name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResourceId {
+    Numeric(u16),
+    /// a name like `LICENSE`, Windows compares names case insensitive
+    Named(String),
+}
+
+impl From<u16> for ResourceId {
+    fn from(value: u16) -> Self {
+        Self::Numeric(value)
+    }
+}
+
+impl From<&str> for ResourceId {
+    fn from(value: &str) -> Self {
+        Self::Named(value.to_owned())
+    }
+}
+
+impl ResourceId {
+    /// whether both ids name the same resource, names are compared case insensitive like Windows does
+    fn same_as(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Named(name), Self::Named(other)) => name.eq_ignore_ascii_case(other),
+            _ => self == other,
+        }
+    }
+}
+
+impl core::fmt::Display for ResourceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Numeric(id) => write!(f, "{id}"),
+            Self::Named(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// The content of a raw resource
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RawData {
+    /// a file, relative to the root of the crate.
+    /// It is copied into `OUT_DIR`, so its path may contain spaces and non-ASCII characters
+    File(Utf8PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// A problem preventing a raw resource from being linked
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RawError {
+    /// a named type or id that is empty, starts with a digit,
+    /// has characters other than ASCII letters, digits and `_` or is a resource script keyword
+    InvalidName(String),
+    /// a numeric type reserved for the predefined types
+    ReservedType(u16),
+    /// the name of the constant is not a Rust identifier or is a keyword
+    InvalidConstant(String),
+    /// two resources have constants with the same name
+    DuplicateConstant(String),
+    /// two resources have the same type, id and language, names are compared case insensitive
+    Duplicate {
+        resource_type: ResourceType,
+        id: ResourceId,
+        lang: Language,
+    },
+}
+
+impl core::fmt::Display for RawError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidName(name) => write!(
+                f,
+                "the resource name `{name}` has to consist of ASCII letters, digits and `_`, must not start with a digit and must not be a keyword"
+            ),
+            Self::ReservedType(resource_type) => {
+                write!(f, "the resource type {resource_type} is reserved for the predefined types")
+            }
+            Self::InvalidConstant(name) => write!(f, "the constant name `{name}` is not a Rust identifier or a keyword"),
+            Self::DuplicateConstant(name) => write!(f, "the constant name `{name}` is used twice"),
+            Self::Duplicate { resource_type, id, lang } => write!(
+                f,
+                "the resource {id} of the type {} and the language {lang:?} is linked twice",
+                resource_type.script_name()
+            ),
+        }
+    }
+}
+
+impl ResourceType {
+    /// whether both types are the same, names are compared case insensitive like Windows does
+    fn same_as(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Named(name), Self::Named(other)) => name.eq_ignore_ascii_case(other),
+            _ => self == other,
+        }
+    }
+
+    /// the type as written in a resource script
+    fn script_name(&self) -> String {
+        match self {
+            Self::RcData => "RCDATA".to_owned(),
+            Self::Numeric(resource_type) => resource_type.to_string(),
+            Self::Named(name) => name.clone(),
+        }
+    }
+}
+
+/// A file or bytes embedded as resource.
+/// The language defaults to `Language::Neutral`, which `FindResourceW` finds for every user language
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawResource {
+    pub resource_type: ResourceType,
+    pub id: ResourceId,
+    pub lang: Language,
+    pub data: RawData,
+    /// the name of the constant for the id written by `RawResources::ids_module`
+    pub constant: Option<String>,
+}
+
+impl RawResource {
+    /// the file as `RCDATA` resource with the given id
+    pub fn file<I: Into<ResourceId>, P: AsRef<Utf8Path>>(id: I, path: P) -> Self {
+        Self::new(id.into(), RawData::File(path.as_ref().to_owned()))
+    }
+
+    /// the bytes as `RCDATA` resource with the given id
+    pub fn bytes<I: Into<ResourceId>, B: Into<Vec<u8>>>(id: I, bytes: B) -> Self {
+        Self::new(id.into(), RawData::Bytes(bytes.into()))
+    }
+
+    fn new(id: ResourceId, data: RawData) -> Self {
+        Self {
+            resource_type: ResourceType::RcData,
+            id,
+            lang: Language::Neutral,
+            data,
+            constant: None,
+        }
+    }
+
+    pub fn with_type(mut self, resource_type: ResourceType) -> Self {
+        self.resource_type = resource_type;
+        self
+    }
+
+    pub fn with_language(mut self, lang: Language) -> Self {
+        self.lang = lang;
+        self
+    }
+
+    /// names the constant of the id, the name has to be a valid Rust identifier and must not be a keyword
+    pub fn with_constant(mut self, name: &str) -> Self {
+        self.constant = Some(name.to_owned());
+        self
+    }
+
+    /// the resource script statement embedding the file `file` in `OUT_DIR`
+    pub(crate) fn script(&self, file: &str) -> String {
+        let id = self.lang.id();
+        format!(
+            "LANGUAGE {:#X}, {:#X}\n{} {} \"{file}\"\n",
+            id & 0x3FF,
+            id >> 10,
+            self.id,
+            self.resource_type.script_name()
+        )
+    }
+}
+
+/// Several raw resources linked together.
+/// ```
+/// use windows_exe_info::raw::*;
+/// let resources = RawResources::new()
+///     .with(RawResource::bytes(1, "key = value").with_constant("CONFIG"))
+///     .with(RawResource::bytes("LICENSE", "MIT").with_type(ResourceType::Named("TEXT".into())));
+/// assert!(resources.validate().is_empty());
+/// assert!(resources.ids_module().contains("pub const CONFIG: u16 = 1;"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RawResources {
+    pub resources: Vec<RawResource>,
+}
+
+impl RawResources {
+    /// no resources
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, resource: RawResource) -> Self {
+        self.resources.push(resource);
+        self
+    }
+
+    /// Checks the names, types and constants of the resources and finds duplicates
+    pub fn validate(&self) -> Vec<RawError> {
+        let mut errors = Vec::new();
+        for (index, resource) in self.resources.iter().enumerate() {
+            if let ResourceType::Named(name) = &resource.resource_type {
+                if !is_resource_name(name) {
+                    errors.push(RawError::InvalidName(name.clone()));
+                }
+            }
+            if let ResourceType::Numeric(resource_type @ 0..=255) = resource.resource_type {
+                errors.push(RawError::ReservedType(resource_type));
+            }
+            if let ResourceId::Named(name) = &resource.id {
+                if !is_resource_name(name) {
+                    errors.push(RawError::InvalidName(name.clone()));
+                }
+            }
+            if let Some(constant) = &resource.constant {
+                if !crate::is_identifier(constant) {
+                    errors.push(RawError::InvalidConstant(constant.clone()));
+                }
+                if self.resources[..index]
+                    .iter()
+                    .any(|other| other.constant.as_ref() == Some(constant))
+                {
+                    errors.push(RawError::DuplicateConstant(constant.clone()));
+                }
+            }
+            if self.resources[..index].iter().any(|other| other.same_resource(resource)) {
+                errors.push(resource.duplicate());
+            }
+        }
+        errors
+    }
+
+    /// Copies the files and writes the bytes into `OUT_DIR` and links them to the binaries.
+    /// Fails if the resources are invalid (see `validate`), a file can not be read or one of them was already linked,
+    /// the problems are shown as build script warnings
+    pub fn link(&self) -> Result<(), &str> {
+        self.link_for(&Artifact::Bins)
+    }
+
+    /// like `link`, but links the resources only to the given artifact
+    pub fn link_for(&self, artifact: &Artifact) -> Result<(), &str> {
+        self.check()?;
+        let data = self.read_data()?;
+        {
+            let mut linked = LINKED_RAW_RESOURCES.lock().unwrap();
+            for resource in &self.resources {
+                if linked.iter().any(|(other_artifact, resource_type, id, lang)| {
+                    other_artifact.overlaps(artifact)
+                        && resource_type.same_as(&resource.resource_type)
+                        && id.same_as(&resource.id)
+                        && *lang == resource.lang
+                }) {
+                    println!("cargo:warning={}", resource.duplicate());
+                    return Err("A raw resource was already linked, see the build script warnings");
+                }
+            }
+            linked.extend(self.resources.iter().map(|resource| {
+                (artifact.clone(), resource.resource_type.clone(), resource.id.clone(), resource.lang)
+            }));
+        }
+
+        let file = CURRENT_RAW_FILE.fetch_add(1, Ordering::Relaxed);
+        let build_res_file =
+            self.write_files(&super::link::out_dir(), file, &data, super::link::script_encoding());
+
+        super::link::link_for(build_res_file, artifact);
+        Ok(())
+    }
+
+    /// the content of each resource, fails if a file can not be read
+    pub(crate) fn read_data(&self) -> Result<Vec<Cow<'_, [u8]>>, &'static str> {
+        self.resources
+            .iter()
+            .map(|resource| match &resource.data {
+                RawData::File(path) => std::fs::read(path).map(Cow::Owned).map_err(|error| {
+                    println!("cargo:warning=raw resource {path}: {error}");
+                    "A raw resource file can not be read, see the build script warnings"
+                }),
+                RawData::Bytes(bytes) => Ok(Cow::Borrowed(bytes.as_slice())),
+            })
+            .collect()
+    }
+
+    /// writes the contents `data` of the resources and the resource script number `file` into `dir`
+    /// and returns the path of the script
+    pub(crate) fn write_files(
+        &self,
+        dir: &Utf8Path,
+        file: u16,
+        data: &[Cow<'_, [u8]>],
+        encoding: ScriptEncoding,
+    ) -> Utf8PathBuf {
+        let mut script = String::new();
+        for (index, (resource, data)) in self.resources.iter().zip(data).enumerate() {
+            // the data is referenced by a generated ASCII name relative to the script
+            let name = format!("raw{file}-{index}.bin");
+            super::link::write_file_in(dir, &name, data);
+            script.push_str(&resource.script(&name));
+        }
+        super::link::write_file_in(dir, &format!("raw{file}.rc"), &encoding.encode(&script))
+    }
+
+    /// Writes the constants of `ids_module` into the file `name` in `OUT_DIR`,
+    /// to be included with `include!(concat!(env!("OUT_DIR"), "/NAME"))`.
+    /// Fails if the resources are invalid, see `validate`
+    pub fn write_ids(&self, name: &str) -> Result<(), &str> {
+        self.check()?;
+        super::link::write_out_file(name, self.ids_module().as_bytes());
+        Ok(())
+    }
+
+    /// a Rust module with a constant per resource with a constant name,
+    /// `u16` for numeric ids and `&str` for named ids
+    pub fn ids_module(&self) -> String {
+        let mut module = String::new();
+        module.push_str("// This file was autogenerated\n// Do not change manually!!!\n");
+        for resource in &self.resources {
+            match (&resource.constant, &resource.id) {
+                (Some(constant), ResourceId::Numeric(id)) => {
+                    module.push_str(&format!("pub const {constant}: u16 = {id};\n"))
+                }
+                (Some(constant), ResourceId::Named(name)) => {
+                    module.push_str(&format!("pub const {constant}: &str = \"{name}\";\n"))
+                }
+                (None, _) => {}
+            }
+        }
+        module
+    }
+
+    /// shows the problems found by `validate` as build script warnings
+    fn check(&self) -> Result<(), &'static str> {
+        let errors = self.validate();
+        for error in &errors {
+            println!("cargo:warning=raw resource: {error}");
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err("Invalid raw resources, see the build script warnings")
+        }
+    }
+}
+
+impl RawResource {
+    fn same_resource(&self, other: &Self) -> bool {
+        self.resource_type.same_as(&other.resource_type) && self.id.same_as(&other.id) && self.lang == other.lang
+    }
+
+    fn duplicate(&self) -> RawError {
+        RawError::Duplicate {
+            resource_type: self.resource_type.clone(),
+            id: self.id.clone(),
+            lang: self.lang,
+        }
+    }
+}
+
+/// keywords of resource scripts, which can not be used as names
+const KEYWORDS: &[&str] = &[
+    "ACCELERATORS", "ANICURSOR", "ANIICON", "BEGIN", "BITMAP", "CURSOR", "DIALOG", "DIALOGEX", "DLGINCLUDE",
+    "END", "FONT", "HTML", "ICON", "LANGUAGE", "MANIFEST", "MENU", "MENUEX", "MESSAGETABLE", "PLUGPLAY",
+    "RCDATA", "STRINGTABLE", "TEXTINCLUDE", "VERSIONINFO", "VXD",
+];
+
+/// names in resource scripts are unquoted, so only plain ASCII names are safe
+fn is_resource_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|first| !first.is_ascii_digit())
+        && name.chars().all(|c| c == '_' || c.is_ascii_alphanumeric())
+        && !KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(name))
+}
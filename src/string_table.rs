@@ -0,0 +1,232 @@
+//! String table resources for localized UI strings, loaded at runtime with `LoadStringW`
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as FmtWrite;
+use std::sync::Mutex;
+
+use crate::versioninfo::{Language, RCString, Severity};
+use crate::{Artifact, ScriptEncoding};
+
+/// the artifacts a string table has been linked to
+pub(crate) static LINKED_STRING_TABLES: Mutex<Vec<Artifact>> = Mutex::new(Vec::new());
+
+/// A problem found by [`StringTable::validate`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// the name of the id is not a Rust identifier or is a keyword
+    InvalidName { name: String, id: u16 },
+    /// the named id has no string in any language
+    MissingId { name: String, id: u16 },
+    /// the language has no string with an id other languages have,
+    /// `LoadStringW` returns 0 for it
+    MissingString { lang: Language, id: u16 },
+}
+
+impl Diagnostic {
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::InvalidName { .. } | Self::MissingId { .. } => Severity::Error,
+            Self::MissingString { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidName { name, id } => write!(f, "the name `{name}` of the string id {id} is not a Rust identifier or a keyword"),
+            Self::MissingId { name, id } => write!(f, "the string id `{name}` ({id}) has no string in any language"),
+            Self::MissingString { lang, id } => write!(f, "the string {id} is missing in {lang:?}"),
+        }
+    }
+}
+
+/// The strings of one language
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalizedStrings {
+    pub lang: Language,
+    /// the strings by id
+    pub strings: BTreeMap<u16, RCString>,
+}
+
+/// The strings of the `STRINGTABLE` resources of an artifact and the names of their ids.
+/// Implements `Display` writing the resource script,
+/// the strings are grouped into the blocks of 16 ids Windows stores them in.
+/// ```
+/// use windows_exe_info::string_table::StringTable;
+/// use windows_exe_info::versioninfo::Language;
+/// let table = StringTable::new()
+///     .with_strings(Language::USEnglish, [(101, "Open"), (102, "Save")])
+///     .with_strings(Language::German, [(101, "Öffnen"), (102, "Speichern")])
+///     .with_id("IDS_OPEN", 101)
+///     .with_id("IDS_SAVE", 102);
+/// assert!(table.ids_module().contains("pub const IDS_SAVE: u32 = 102;"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StringTable {
+    /// the strings of each language, in the order they were added
+    pub languages: Vec<LocalizedStrings>,
+    /// the names of the constants written by `ids_module`
+    pub ids: BTreeMap<String, u16>,
+}
+
+impl StringTable {
+    /// a table without strings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds the id and string pairs to the strings of the language, replacing strings with the same id
+    pub fn with_strings<I, S>(mut self, lang: Language, strings: I) -> Self
+    where
+        I: IntoIterator<Item = (u16, S)>,
+        S: Into<RCString>,
+    {
+        let index = match self.languages.iter().position(|localized| localized.lang == lang) {
+            Some(index) => index,
+            None => {
+                self.languages.push(LocalizedStrings {
+                    lang,
+                    strings: BTreeMap::new(),
+                });
+                self.languages.len() - 1
+            }
+        };
+        self.languages[index]
+            .strings
+            .extend(strings.into_iter().map(|(id, string)| (id, string.into())));
+        self
+    }
+
+    /// names the id, the name has to be a valid Rust identifier and must not be a keyword
+    pub fn with_id(mut self, name: &str, id: u16) -> Self {
+        self.ids.insert(name.to_owned(), id);
+        self
+    }
+
+    /// Checks the table for problems, see [`Diagnostic`]
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for (name, &id) in &self.ids {
+            if !crate::is_identifier(name) {
+                diagnostics.push(Diagnostic::InvalidName { name: name.clone(), id });
+            }
+            if !self.languages.iter().any(|localized| localized.strings.contains_key(&id)) {
+                diagnostics.push(Diagnostic::MissingId { name: name.clone(), id });
+            }
+        }
+        let all_ids: BTreeSet<u16> = self
+            .languages
+            .iter()
+            .flat_map(|localized| localized.strings.keys().copied())
+            .collect();
+        for localized in &self.languages {
+            diagnostics.extend(
+                all_ids
+                    .iter()
+                    .filter(|id| !localized.strings.contains_key(id))
+                    .map(|&id| Diagnostic::MissingString { lang: localized.lang, id }),
+            );
+        }
+        diagnostics
+    }
+
+    /// Writes the resource script into `OUT_DIR` and links it to the binaries.
+    /// Fails if a string table was already linked.
+    /// The table is validated first, warnings are shown as build script warnings and errors prevent linking
+    pub fn link(&self) -> Result<(), &str> {
+        self.link_for(&Artifact::Bins)
+    }
+
+    /// like `link`, but links the string table only to the given artifact
+    pub fn link_for(&self, artifact: &Artifact) -> Result<(), &str> {
+        if LINKED_STRING_TABLES
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|linked| linked.overlaps(artifact))
+        {
+            return Err("Only one string table can be linked per artifact");
+        }
+        self.check()?;
+        let build_res_file = super::link::write_out_file(
+            &format!("strings{}.rc", artifact.file_suffix()),
+            &self.resource_script_as(super::link::script_encoding()),
+        );
+
+        super::link::link_for(build_res_file, artifact);
+        LINKED_STRING_TABLES.lock().unwrap().push(artifact.clone());
+        Ok(())
+    }
+
+    /// Writes the constants of `ids_module` into the file `name` in `OUT_DIR`,
+    /// to be included with `include!(concat!(env!("OUT_DIR"), "/NAME"))`.
+    /// Fails if the table has errors, see `validate`
+    pub fn write_ids(&self, name: &str) -> Result<(), &str> {
+        self.check()?;
+        super::link::write_out_file(name, self.ids_module().as_bytes());
+        Ok(())
+    }
+
+    /// a Rust module with a `u32` constant per named id, the type `LoadStringW` takes
+    pub fn ids_module(&self) -> String {
+        let mut module = String::new();
+        module.push_str("// This file was autogenerated\n// Do not change manually!!!\n");
+        for (name, id) in &self.ids {
+            module.push_str(&format!("pub const {name}: u32 = {id};\n"));
+        }
+        module
+    }
+
+    /// shows the problems found by `validate` as build script warnings
+    fn check(&self) -> Result<(), &'static str> {
+        let diagnostics = self.validate();
+        for diagnostic in &diagnostics {
+            println!("cargo:warning=string table: {diagnostic}");
+        }
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity() == Severity::Error)
+        {
+            return Err("Invalid string table, see the build script warnings");
+        }
+        Ok(())
+    }
+
+    /// the resource script encoded using `encoding`, read as UTF-8 if it is not UTF-16
+    pub(crate) fn resource_script_as(&self, encoding: ScriptEncoding) -> Vec<u8> {
+        match encoding.resolve() {
+            ScriptEncoding::Utf16Le => encoding.encode(&self.to_string()),
+            _ => format!("#pragma code_page(65001)\n{self}").into_bytes(),
+        }
+    }
+}
+
+impl core::fmt::Display for StringTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut result = String::new();
+        writeln!(result, "// This resource script was autogenerated")?;
+        writeln!(result, "// Do not change manually!!!")?;
+        for localized in &self.languages {
+            let id = localized.lang.id();
+            // primary and sub language of the language id
+            writeln!(result, "LANGUAGE {:#X}, {:#X}", id & 0x3FF, id >> 10)?;
+            let mut block = None;
+            for (id, string) in &localized.strings {
+                // Windows stores the strings in blocks of the 16 ids sharing `id / 16`
+                if block != Some(id >> 4) {
+                    if block.is_some() {
+                        writeln!(result, "END")?;
+                    }
+                    block = Some(id >> 4);
+                    writeln!(result, "STRINGTABLE")?;
+                    writeln!(result, "BEGIN")?;
+                }
+                writeln!(result, r#" {id}, "{}""#, string.escaped())?;
+            }
+            if block.is_some() {
+                writeln!(result, "END")?;
+            }
+        }
+        write!(f, "{result}")
+    }
+}
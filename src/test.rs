@@ -73,16 +73,19 @@ fn format_version_info() {
             product_version: "To be decided".into(),
             private_build: None,
             special_build: Some("Made for testing, who would have guessed".into()),
+            custom: Default::default(),
         }],
+        var_file_info: VarFileInfo::Auto,
     };
     // check formatting
     assert_eq!(rc.to_string(), FORMATTED_VERSIONINFO);
 
     // check double linking prevention
-    assert!(!HAS_LINKED_VERSION_INFO.load(Ordering::Relaxed));
+    assert!(LINKED_VERSION_INFO.lock().unwrap().is_empty());
     rc.link().unwrap();
-    assert!(HAS_LINKED_VERSION_INFO.load(Ordering::Relaxed));
+    assert_eq!(*LINKED_VERSION_INFO.lock().unwrap(), [crate::Artifact::Bins]);
     assert!(rc.link().is_err());
+    assert!(rc.link_for(&crate::Artifact::bin("test")).is_err());
 
     // cleanup
 
@@ -126,6 +129,10 @@ fn multi_icon_id() {
         assert!(temp_file.exists());
         std::fs::remove_file(&temp_file).unwrap();
         assert!(temp_file.pop());
+        temp_file.push(format!("icon{i}.ico"));
+        assert!(temp_file.exists());
+        std::fs::remove_file(&temp_file).unwrap();
+        assert!(temp_file.pop());
         // cleanup
         #[cfg(feature = "embed_resource")]
         {
@@ -150,3 +157,1296 @@ fn multi_icon_id() {
     // check (2)
     assert_eq!(CURRENT_ICON_ID.load(Ordering::Relaxed), ITERATIONS);
 }
+
+#[cfg(feature = "metadata")]
+#[test]
+fn parse_package_metadata() {
+    use crate::metadata::{Metadata, MetadataError};
+
+    let (metadata, bins) = Metadata::parse(
+        r#"
+[package]
+name = "example"
+
+[package.metadata.windows_exe_info]
+icon = "icon.ico"
+company-name = "Example Corp"
+strings = { Homepage = "https://example.com" }
+
+[package.metadata.windows_exe_info.bin.example-cli]
+icon = "cli.ico"
+
+[[bin]]
+name = "example-cli"
+"#,
+        "missing".into(),
+    )
+    .unwrap();
+    assert_eq!(bins, ["example-cli"]);
+    assert_eq!(metadata.icon.as_deref(), Some("icon.ico".into()));
+    assert_eq!(metadata.company_name.as_deref(), Some("Example Corp"));
+    assert_eq!(metadata.strings["Homepage"], "https://example.com");
+    assert_eq!(metadata.bin["example-cli"].icon.as_deref(), Some("cli.ico".into()));
+
+    // unknown keys are rejected
+    let error = Metadata::parse(
+        r#"
+[package]
+name = "example"
+
+[package.metadata.windows_exe_info]
+company = "Example Corp"
+"#,
+        "missing".into(),
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(error, MetadataError::Toml(_)));
+    assert!(error.to_string().contains("company"));
+
+    // the binaries are found the way cargo finds them
+    let root = camino::Utf8PathBuf::try_from(std::env::temp_dir().join("windows_exe_info_bins")).unwrap();
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("src/bin/server")).unwrap();
+    for file in ["src/main.rs", "src/bin/tool.rs", "src/bin/server/main.rs"] {
+        std::fs::write(root.join(file), "fn main() {}").unwrap();
+    }
+    let bins = |cargo_toml: &str| Metadata::parse(cargo_toml, &root).unwrap().1;
+    assert_eq!(bins("[package]\nname = \"example\"\n"), ["example", "server", "tool"]);
+    // a renamed `src/main.rs` does not also get the package name
+    let renamed = "[package]\nname = \"example\"\n\n[[bin]]\nname = \"cli\"\npath = \"src/main.rs\"\n";
+    assert_eq!(bins(renamed), ["cli", "server", "tool"]);
+    assert_eq!(bins(&renamed.replace("src/main.rs", "./src/main.rs")), ["cli", "server", "tool"]);
+    let explicit = "[package]\nname = \"example\"\nautobins = false\n\n[[bin]]\nname = \"cli\"\npath = \"src/main.rs\"\n";
+    assert_eq!(bins(explicit), ["cli"]);
+    // a target named like a discovered one is not added twice
+    assert_eq!(bins("[package]\nname = \"example\"\n\n[[bin]]\nname = \"tool\"\n"), ["example", "server", "tool"]);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserialize_version_info() {
+    let rc: VersionInfo = serde_json::from_str(
+        r#"{
+            "file_version": "1.2.3",
+            "product_version": "1.2.3.4",
+            "file_flag_mask": "Win16",
+            "file_flags": { "prerelease": true },
+            "file_os": "NTWindows32",
+            "file_type": { "Driver": "Printer" },
+            "file_info": [{
+                "lang": "German",
+                "charset": "1200",
+                "company_name": "Beispiel GmbH",
+                "file_description": "Beispiel",
+                "file_version": "1.2.3",
+                "internal_name": "beispiel",
+                "original_filename": "beispiel.exe",
+                "product_name": "Beispiel",
+                "product_version": "1.2.3.4",
+                "custom": { "Homepage": "https://example.com" }
+            }]
+        }"#,
+    )
+    .unwrap();
+    let formatted = rc.to_string();
+    assert!(formatted.contains("FILEVERSION     1, 2, 3, 0\n"));
+    assert!(formatted.contains("FILETYPE        VFT_DRV\nFILESUBTYPE     VFT2_DRV_PRINTER\n"));
+    assert!(formatted.contains(r#"BLOCK "040704B0""#));
+    assert!(formatted.contains(r#"VALUE "Homepage", "https://example.com\0""#));
+
+    let serialized = serde_json::to_value(&rc).unwrap();
+    assert_eq!(serialized["file_info"][0]["lang"], "German");
+    assert_eq!(serialized["file_info"][0]["charset"], "Unicode");
+    assert_eq!(serialized["product_version"], "1.2.3.4");
+
+    assert_eq!("0x0414".parse::<Language>(), Ok(Language::NorwegianBokmal));
+    assert!(serde_json::from_str::<Language>(r#""Klingon""#).is_err());
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn parse_version_info() {
+    // the generated script round-trips
+    let rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+    assert_eq!(rc.to_string(), FORMATTED_VERSIONINFO);
+    assert!(rc.file_flags.specialbuild);
+    assert_eq!(rc.file_info[0].company_name.as_str(), "TEST");
+
+    // hand written scripts with other resources, comments and numeric values
+    let rc: VersionInfo = r#"
+#include <windows.h>
+1 ICON "app.ico"
+/* legacy
+   version resource */
+1 VERSIONINFO
+FILEVERSION 1,2,3,4
+PRODUCTVERSION 1,2
+FILEFLAGSMASK 0x3fL
+FILEFLAGS VS_FF_DEBUG | VS_FF_PRIVATEBUILD
+FILEOS VOS_NT_WINDOWS32
+FILETYPE VFT_DLL
+FILESUBTYPE VFT2_UNKNOWN
+{
+    BLOCK "StringFileInfo"
+    {
+        BLOCK "040704b0"
+        {
+            VALUE "CompanyName", "Beispiel ""GmbH"""
+            VALUE "FileDescription", L"C:\\Programme\\Beispiel"
+            VALUE "PrivateBuild", "Built by " "TESTER1"
+            VALUE "Homepage", "https://example.com\0"
+        }
+    }
+    BLOCK "VarFileInfo"
+    {
+        VALUE "Translation", 0x407, 1200
+    }
+}
+"#
+    .parse()
+    .unwrap();
+    assert_eq!(rc.file_version, Version(1, 2, 3, 4));
+    assert_eq!(rc.product_version, Version(1, 2, 0, 0));
+    assert_eq!(rc.file_flag_mask, FileFlagMask::Win16);
+    assert!(rc.file_flags.debug && rc.file_flags.privatebuild && !rc.file_flags.specialbuild);
+    assert_eq!(rc.file_os, FileOS::NTWindows32);
+    assert_eq!(rc.file_type, FileType::Dll);
+    let file_info = &rc.file_info[0];
+    assert_eq!(file_info.lang, Language::German);
+    assert_eq!(file_info.charset, CharacterSet::Unicode);
+    assert_eq!(file_info.company_name.as_str(), r#"Beispiel "GmbH""#);
+    assert_eq!(file_info.file_description.as_str(), r"C:\Programme\Beispiel");
+    assert_eq!(file_info.private_build.as_ref().unwrap().as_str(), "Built by TESTER1");
+    assert_eq!(file_info.custom["Homepage"].as_str(), "https://example.com");
+    assert_eq!(VersionInfo::from_rc(&rc.to_string()), Ok(rc));
+
+    let error = VersionInfo::from_rc("1 VERSIONINFO\nFILEVERSION 1,2,3,4\nFILEOS VOS_MARS\nBEGIN\nEND").unwrap_err();
+    assert_eq!(error.line, 3);
+    // both branches of a condition would be read, so conditions are rejected
+    let error = VersionInfo::from_rc("#include <winver.h>\n#ifdef DEBUG\n1 VERSIONINFO\nFILEFLAGS VS_FF_DEBUG\nBEGIN\nEND\n#endif").unwrap_err();
+    assert_eq!(error.line, 2);
+    let error = VersionInfo::from_rc_file("windows_exe_info_missing.rc").unwrap_err();
+    assert_eq!(error.line, 0);
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn binary_version_info() {
+    let mut rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+
+    // round trip
+    let bytes = rc.to_bytes().unwrap();
+    assert_eq!(bytes.len() % 4, 0);
+    assert_eq!(VersionInfo::from_bytes(&bytes), Ok(rc.clone()));
+
+    // the string "CompanyName" = "TEST" is 42 bytes long, its value 5 characters
+    let key: Vec<u8> = "CompanyName".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let position = bytes.windows(key.len()).position(|window| window == key).unwrap();
+    assert_eq!(bytes[position - 6..position], [42, 0, 5, 0, 1, 0]);
+    let value: Vec<u8> = "TEST\0".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    assert_eq!(bytes[position + 24..position + 26], [0, 0]);
+    assert_eq!(bytes[position + 26..position + 36], value);
+
+    // without string tables only the fixed file info is written
+    rc.file_info.clear();
+    let bytes = rc.to_bytes().unwrap();
+    assert_eq!(bytes.len(), 92);
+    assert_eq!(bytes[..6], [92, 0, 52, 0, 0, 0]);
+    assert_eq!(bytes[40..44], [0xBD, 0x04, 0xEF, 0xFE]);
+    // FILEVERSION 0, 1, 2, 3
+    assert_eq!(bytes[48..56], [1, 0, 0, 0, 3, 0, 2, 0]);
+    // FILEFLAGSMASK, FILEFLAGS VS_FF_SPECIALBUILD, FILEOS VOS_NT, FILETYPE VFT_APP
+    assert_eq!(bytes[64..80], [0x3F, 0, 0, 0, 0x20, 0, 0, 0, 0, 0, 4, 0, 1, 0, 0, 0]);
+    assert_eq!(VersionInfo::from_bytes(&bytes), Ok(rc.clone()));
+
+    let res = rc.to_res().unwrap();
+    assert_eq!(res[32..36], 92u32.to_le_bytes());
+    assert_eq!(res[64..], bytes);
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn malformed_binary_version_info() {
+    let mut rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+    let utf16 = |text: &str| -> Vec<u8> { text.encode_utf16().flat_map(u16::to_le_bytes).collect() };
+
+    // a string table key whose fifth byte is inside a multi byte character
+    let mut bytes = rc.to_bytes().unwrap();
+    let key = utf16("040904E4");
+    let position = bytes.windows(key.len()).position(|window| window == key).unwrap();
+    bytes[position..position + key.len()].copy_from_slice(&utf16("abcä0000"));
+    let error = VersionInfo::from_bytes(&bytes).unwrap_err();
+    assert!(error.message.contains("invalid string table key"), "{error}");
+
+    // node lengths are 16 bit
+    rc.file_info[0].custom.insert("Long".into(), "x".repeat(40_000).into());
+    assert_eq!(rc.to_bytes().unwrap_err().key, "Long");
+    assert!(rc.to_res().is_err());
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn validate_version_info() {
+    let mut rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+    rc.file_version = Version(1, 2, 3, 4);
+    rc.file_info[0].product_version = "4.5.6.7".into();
+    assert_eq!(rc.validate(), []);
+
+    rc.file_flags.privatebuild = true;
+    rc.file_flags.specialbuild = false;
+    rc.file_info[0].file_version = "1.3".into();
+    rc.file_info.push(rc.file_info[0].clone());
+    let diagnostics = rc.validate();
+    let (lang, charset) = (Language::USEnglish, CharacterSet::Multilingual);
+    assert_eq!(
+        diagnostics[..3],
+        [
+            Diagnostic::MissingPrivateBuild { lang, charset },
+            Diagnostic::UnexpectedSpecialBuild { lang, charset },
+            Diagnostic::FileVersionMismatch {
+                lang,
+                charset,
+                string: "1.3".into(),
+                version: Version(1, 2, 3, 4)
+            },
+        ]
+    );
+    assert_eq!(diagnostics.last(), Some(&Diagnostic::DuplicateTranslation { lang, charset }));
+    assert_eq!(diagnostics[0].severity(), Severity::Error);
+    assert_eq!(diagnostics[1].severity(), Severity::Warning);
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn numeric_version_info_values() {
+    let flags = [
+        (FileFlags::DEBUG, 0x01, "VS_FF_DEBUG"),
+        (FileFlags::PRERELEASE, 0x02, "VS_FF_PRERELEASE"),
+        (FileFlags::PATCHED, 0x04, "VS_FF_PATCHED"),
+        (FileFlags::PRIVATEBUILD, 0x08, "VS_FF_PRIVATEBUILD"),
+        (FileFlags::INFOINFERRED, 0x10, "VS_FF_INFOINFERRED"),
+        (FileFlags::SPECIALBUILD, 0x20, "VS_FF_SPECIALBUILD"),
+    ];
+    for (flag, value, name) in flags {
+        assert_eq!(u32::from(flag), value);
+        assert_eq!(FileFlags::from(value), flag);
+        assert_eq!(flag.to_string(), name);
+    }
+    let mut flags = FileFlags::DEBUG | FileFlags::PATCHED;
+    assert_eq!(u32::from(flags), 0x05);
+    assert!(flags.contains(FileFlags::PATCHED) && !flags.contains(FileFlags::PRERELEASE));
+    flags &= !FileFlags::DEBUG;
+    assert_eq!(flags, FileFlags::PATCHED);
+    assert_eq!(u32::from(!FileFlags::NONE), 0x3F);
+    assert_eq!(format!("{:#}", FileFlags::ALL), "0x3F");
+
+    assert_eq!(u32::from(FileFlagMask::Win16), 0x3F);
+    assert_eq!(FileFlagMask::from(0x3F), FileFlagMask::Win16);
+    assert_eq!(FileFlagMask::from(0x0F), FileFlagMask::Custom(0x0F));
+
+    let os = [
+        (FileOS::Unknown, 0x0000_0000),
+        (FileOS::Dos, 0x0001_0000),
+        (FileOS::OS216Bit, 0x0002_0000),
+        (FileOS::OS232Bit, 0x0003_0000),
+        (FileOS::NT, 0x0004_0000),
+        (FileOS::WinCE, 0x0005_0000),
+        (FileOS::Windows16, 0x0000_0001),
+        (FileOS::PM16, 0x0000_0002),
+        (FileOS::PM32, 0x0000_0003),
+        (FileOS::Windows32, 0x0000_0004),
+        (FileOS::DosWindows16, 0x0001_0001),
+        (FileOS::DosWindows32, 0x0001_0004),
+        (FileOS::OS216BitPM16, 0x0002_0002),
+        (FileOS::OS232BitPM32, 0x0003_0003),
+        (FileOS::NTWindows32, 0x0004_0004),
+    ];
+    for (file_os, value) in os {
+        assert_eq!(u32::from(file_os), value);
+        assert_eq!(FileOS::from(value), file_os);
+    }
+    assert_eq!(FileOS::NT | FileOS::Windows32, FileOS::NTWindows32);
+    assert_eq!(FileOS::NTWindows32.os(), FileOS::NT);
+    assert_eq!(FileOS::NTWindows32.window_system(), FileOS::Windows32);
+
+    let types = [
+        (FileType::Unknown, 0, 0),
+        (FileType::App, 1, 0),
+        (FileType::Dll, 2, 0),
+        (FileType::Driver(SubTypeDriver::Printer), 3, 0x1),
+        (FileType::Driver(SubTypeDriver::Keyboard), 3, 0x2),
+        (FileType::Driver(SubTypeDriver::Language), 3, 0x3),
+        (FileType::Driver(SubTypeDriver::Display), 3, 0x4),
+        (FileType::Driver(SubTypeDriver::Mouse), 3, 0x5),
+        (FileType::Driver(SubTypeDriver::Network), 3, 0x6),
+        (FileType::Driver(SubTypeDriver::System), 3, 0x7),
+        (FileType::Driver(SubTypeDriver::Installable), 3, 0x8),
+        (FileType::Driver(SubTypeDriver::Sound), 3, 0x9),
+        (FileType::Driver(SubTypeDriver::Comm), 3, 0xA),
+        (FileType::Driver(SubTypeDriver::InputMethod), 3, 0xB),
+        (FileType::Driver(SubTypeDriver::VersionedPrinter), 3, 0xC),
+        (FileType::Font(SubTypeFont::RasterFont), 4, 0x1),
+        (FileType::Font(SubTypeFont::VectorFont), 4, 0x2),
+        (FileType::Font(SubTypeFont::TrueTypeFont), 4, 0x3),
+        (FileType::VXD, 5, 0),
+        (FileType::StaticLibrary, 7, 0),
+    ];
+    for (file_type, value, subtype) in types {
+        assert_eq!(u32::from(file_type), value);
+        assert_eq!(file_type.subtype_value(), subtype);
+        assert_eq!(FileType::from_values(value, subtype), file_type);
+    }
+    assert_eq!(FileType::from(2), FileType::Dll);
+
+    // the numeric script does not depend on winver.h
+    let rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+    let numeric = format!("{rc:#}");
+    assert!(!numeric.contains("winver.h") && !numeric.contains("VS_"));
+    assert!(numeric.contains("FILEOS          0x40000\n"));
+    assert_eq!(VersionInfo::from_rc(&numeric).unwrap(), rc);
+}
+
+#[test]
+fn artifact_overlaps() {
+    use crate::Artifact;
+    let cli = Artifact::bin("cli");
+    assert!(Artifact::All.overlaps(&cli) && cli.overlaps(&Artifact::All));
+    assert!(Artifact::Bins.overlaps(&cli));
+    assert!(cli.overlaps(&Artifact::bin("cli")));
+    assert!(!cli.overlaps(&Artifact::bin("gui")));
+    assert!(!cli.overlaps(&Artifact::Cdylib));
+    assert!(!Artifact::Bins.overlaps(&Artifact::Cdylib));
+    assert_eq!(cli.file_suffix(), "-bin-cli");
+    assert_eq!(Artifact::All.file_suffix(), "");
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn derive_copyright() {
+    use crate::versioninfo::package::*;
+    assert_eq!(
+        authors("Jane Doe <jane@example.com>:John Roe:"),
+        ["Jane Doe", "John Roe"]
+    );
+    assert_eq!(year_of(0), 1970);
+    assert_eq!(year_of(951_782_400), 2000); // 2000-02-29
+    assert_eq!(year_of(1_767_225_599), 2025); // 2025-12-31 23:59:59
+    assert_eq!(year_of(1_767_225_600), 2026);
+    assert_eq!(epoch_year(Some(" 1767225600\n")), 2026);
+    // an invalid epoch falls back to the current year instead of failing the build
+    assert_eq!(epoch_year(Some("yesterday")), epoch_year(None));
+    assert!(epoch_year(None) >= 2026);
+    assert_eq!(
+        copyright_notice("2024-2026", "Jane Doe", "MIT"),
+        "Copyright (c) 2024-2026 Jane Doe, licensed under MIT"
+    );
+    assert_eq!(copyright_notice("2026", "Jane Doe", ""), "Copyright (c) 2026 Jane Doe");
+
+    assert_eq!(CompanySource::Fixed("ACME".into()).resolve().as_deref(), Some("ACME"));
+    assert_eq!(CompanySource::Fixed("".into()).resolve(), None);
+    assert_eq!(CopyrightSource::None.resolve(), None);
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn version_info_for_artifact() {
+    use crate::Artifact;
+    let package = std::env::var("CARGO_PKG_NAME").unwrap();
+
+    let dll = VersionInfo::from_cargo_env_for(&Artifact::Cdylib);
+    assert_eq!(dll.file_type, FileType::Dll);
+    assert_eq!(dll.file_info[0].original_filename.as_str(), "windows_exe_info.dll");
+
+    let bin = VersionInfo::from_cargo_env_for(&Artifact::bin("example-cli"));
+    assert_eq!(bin.file_type, FileType::App);
+    assert_eq!(bin.file_info[0].internal_name.as_str(), "example-cli");
+    assert_eq!(bin.file_info[0].original_filename.as_str(), "example-cli.exe");
+    assert_eq!(bin.file_info[0].product_name.as_str(), package);
+
+    let all = VersionInfo::from_cargo_env_for(&Artifact::All);
+    assert_eq!(all, VersionInfo::from_cargo_env());
+}
+
+#[cfg(feature = "git")]
+#[test]
+fn git_version_info() {
+    let tagged = GitInfo::from_describe("v1.2.0-5-g1a2b3c4-dirty", "1a2b3c4d".into());
+    assert_eq!(
+        tagged,
+        GitInfo {
+            commit: "1a2b3c4d".into(),
+            short_commit: "1a2b3c4".into(),
+            dirty: true,
+            tag: Some("v1.2.0".into()),
+            commits_since_tag: 5,
+        }
+    );
+    let untagged = GitInfo::from_describe("1a2b3c4", "1a2b3c4d".into());
+    assert_eq!(untagged.tag, None);
+    assert!(!untagged.dirty);
+    assert_eq!(untagged.description(), "commit 1a2b3c4");
+
+    let rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap().with_git(&tagged);
+    assert_eq!(rc.file_version.3, 5);
+    assert!(rc.file_flags.privatebuild && rc.file_flags.patched);
+    assert_eq!(
+        rc.file_info[0].private_build.as_ref().unwrap().as_str(),
+        "commit 1a2b3c4 (dirty)"
+    );
+
+    // flags and strings set before are kept
+    let mut patched = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+    patched.file_flags.patched = true;
+    patched.file_info[0].private_build = Some("nightly".into());
+    let clean = GitInfo::from_describe("v1.2.0-0-g1a2b3c4", "1a2b3c4d".into());
+    let rc = patched.with_git(&clean);
+    assert!(rc.file_flags.patched && !rc.file_flags.privatebuild);
+    assert_eq!(rc.file_info[0].private_build.as_ref().unwrap().as_str(), "nightly");
+}
+
+#[cfg(feature = "git")]
+#[test]
+fn git_worktree_watched_files() {
+    use camino::Utf8PathBuf;
+    use std::process::Command;
+    let root = std::env::temp_dir().join("windows_exe_info_git_worktree");
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("main")).unwrap();
+    // the paths printed by git have no symbolic links
+    let root = Utf8PathBuf::try_from(std::fs::canonicalize(root).unwrap()).unwrap();
+    let (main, linked) = (root.join("main"), root.join("linked"));
+    let git = |dir: &Utf8PathBuf, args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?}");
+    };
+    git(&main, &["init", "-q", "-b", "main"]);
+    git(&main, &["commit", "-q", "--allow-empty", "-m", "first"]);
+    git(&main, &["worktree", "add", "-q", "-b", "feature", linked.as_str()]);
+
+    let common_dir = main.join(".git");
+    let git_dir = common_dir.join("worktrees").join("linked");
+    assert_eq!(
+        GitInfo::watched_files(&linked).unwrap(),
+        [
+            git_dir.join("HEAD"),
+            git_dir.join("index"),
+            common_dir.join("refs/heads/feature"),
+            common_dir.join("packed-refs"),
+        ]
+    );
+    assert_eq!(GitInfo::read_from(&linked).unwrap().commits_since_tag, 1);
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn reproducible_output() {
+    use crate::versioninfo::CopyrightSource;
+    use camino::Utf8PathBuf;
+    let out_dir = Utf8PathBuf::try_from(std::env::temp_dir().join("windows_exe_info_reproducible")).unwrap();
+    let _ = std::fs::remove_dir_all(&out_dir);
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let copyright = CopyrightSource::Authors
+        .resolve_with("Jane Doe <jane@example.com>", "MIT", Some("1700000000"))
+        .unwrap();
+    assert_eq!(copyright, "Copyright (c) 2023 Jane Doe, licensed under MIT");
+
+    let mut rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+    rc.file_info[0].legal_copyright = Some(copyright.as_str().into());
+    let build = || {
+        let script = crate::link::write_file_in(
+            &out_dir,
+            "reproducible.rc",
+            &rc.resource_script_as(crate::ScriptEncoding::Utf8).unwrap(),
+        );
+        let modified = std::fs::metadata(&script).unwrap().modified().unwrap();
+        (std::fs::read(&script).unwrap(), modified, rc.to_res().unwrap())
+    };
+    let (script, modified, res) = build();
+    assert_eq!(build(), (script.clone(), modified, res));
+    let script = String::from_utf8(script).unwrap();
+    assert!(script.contains(r#"VALUE "LegalCopyright", "Copyright (c) 2023 Jane Doe, licensed under MIT\0""#));
+
+    #[cfg(feature = "icon_ico")]
+    {
+        // a real icon linked from an absolute path is referenced only by the name of its copy
+        let icon = Utf8PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("icon.ico");
+        assert!(icon.is_absolute());
+        let name = crate::link::copy_asset_into(&out_dir, &icon, "icon3.ico").unwrap();
+        let script = crate::link::write_file_in(&out_dir, "icon3.rc", crate::icon::icon_script(3, &name).as_bytes());
+        assert_eq!(std::fs::read(out_dir.join("icon3.ico")).unwrap(), std::fs::read(&icon).unwrap());
+        let script = std::fs::read_to_string(script).unwrap();
+        assert_eq!(script, "icon3 ICON \"icon3.ico\"\n");
+        assert!(!script.contains(env!("CARGO_MANIFEST_DIR")) && !script.contains(out_dir.as_str()));
+        assert!(crate::link::copy_asset_into(&out_dir, &out_dir.join("missing.ico"), "icon4.ico").is_err());
+    }
+    std::fs::remove_dir_all(&out_dir).unwrap();
+}
+
+#[cfg(feature = "locale")]
+#[test]
+fn load_locales() {
+    let dir = camino::Utf8PathBuf::try_from(std::env::temp_dir().join("windows_exe_info_locales")).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("de-DE.toml"),
+        "FileDescription = \"Beispielprogramm\"\nHomepage = \"https://example.com/de\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("fr-FR.ftl"),
+        "# comment\nFileDescription = Programme\n    d'exemple\n-charset = 1252\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("ja-JP.toml"), "ProductName = \"サンプル\"\n-charset = 932\ncharset = \"Shift JIS\"\n").unwrap();
+    std::fs::write(dir.join("readme.txt"), "ignored").unwrap();
+
+    let rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO)
+        .unwrap()
+        .with_locales(&dir)
+        .unwrap();
+    let tables: Vec<_> = rc.file_info.iter().map(|file_info| (file_info.lang, file_info.charset)).collect();
+    assert_eq!(
+        tables,
+        [
+            (Language::German, CharacterSet::Unicode),
+            (Language::French, CharacterSet::Multilingual),
+            (Language::Japanese, CharacterSet::JapanShiftJISX0208),
+        ]
+    );
+    assert_eq!(rc.file_info[0].custom["Homepage"].as_str(), "https://example.com/de");
+    assert_eq!(rc.file_info[1].file_description.as_str(), "Programme\nd'exemple");
+    assert_eq!(rc.file_info[2].product_name.as_str(), "サンプル");
+    assert_eq!(rc.file_info[2].custom["charset"].as_str(), "Shift JIS");
+    // strings missing in the files are taken from the base table
+    assert_eq!(rc.file_info[2].company_name.as_str(), "TEST");
+    assert!(rc
+        .to_string()
+        .contains(r#"VALUE "Translation", 0x0407, 1200, 0x040C, 1252, 0x0411, 932"#));
+
+    std::fs::write(dir.join("it-IT.ftl"), "FileDescription = { $name }\n").unwrap();
+    let error = rc.clone().with_locales(&dir).unwrap_err();
+    assert_eq!((error.path.file_name(), error.line), (Some("it-IT.ftl"), 1));
+    std::fs::remove_file(dir.join("it-IT.ftl")).unwrap();
+    // an error at the start of a line is reported on that line, not on the preceding one
+    std::fs::write(dir.join("es-ES.toml"), "ProductName = \"Ejemplo\"\n\n= \"sin nombre\"\n").unwrap();
+    let error = rc.clone().with_locales(&dir).unwrap_err();
+    assert_eq!((error.path.file_name(), error.line), (Some("es-ES.toml"), 3));
+    // values other than strings are reported on their line
+    std::fs::write(dir.join("es-ES.toml"), "ProductName = \"Ejemplo\"\n\nFileDescription = 3\n").unwrap();
+    let error = rc.clone().with_locales(&dir).unwrap_err();
+    assert_eq!((error.line, error.message.as_str()), (3, "`FileDescription` must be a string, not integer"));
+    std::fs::remove_file(dir.join("es-ES.toml")).unwrap();
+    // the term has no continuation lines, they are not added to the message before it
+    std::fs::write(dir.join("it-IT.ftl"), "FileDescription = Programma\n-charset = 1252\n    di esempio\n").unwrap();
+    let error = rc.clone().with_locales(&dir).unwrap_err();
+    assert_eq!((error.path.file_name(), error.line), (Some("it-IT.ftl"), 3));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn encode_string_tables() {
+    let mut rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+    rc.file_info[0].lang = Language::Russian;
+    rc.file_info[0].charset = CharacterSet::Cyrillic;
+    rc.file_info[0].product_name = "Пример".into();
+    let mut japanese = rc.file_info[0].clone();
+    japanese.lang = Language::Japanese;
+    japanese.charset = CharacterSet::JapanShiftJISX0208;
+    japanese.product_name = "サンプル".into();
+    rc.file_info.push(japanese);
+
+    let script = rc.encoded_script().unwrap();
+    let find = |needle: &[u8]| script.windows(needle.len()).position(|window| window == needle);
+    let cyrillic = find(b"#pragma code_page(1251)\n  BLOCK \"041904E3\"").unwrap();
+    let shift_jis = find(b"#pragma code_page(932)\n  BLOCK \"041103A4\"").unwrap();
+    assert!(cyrillic < find(b"\"\xCF\xF0\xE8\xEC\xE5\xF0\\0\"").unwrap());
+    assert!(shift_jis < find(b"\"\x83\x54\x83\x93\x83\x76\x83\x8B\\0\"").unwrap());
+
+    rc.file_info[0].comment = Some("日本".into());
+    let unencodable = Diagnostic::UnencodableString {
+        lang: Language::Russian,
+        charset: CharacterSet::Cyrillic,
+        key: "Comments".into(),
+        character: '日',
+    };
+    assert_eq!(rc.encoded_script(), Err(unencodable.clone()));
+    assert!(rc.validate_for(crate::ScriptEncoding::Utf8).contains(&unencodable));
+    assert_eq!(unencodable.severity(), Severity::Error);
+    // UTF-16 scripts can contain every character
+    assert!(!rc.validate_for(crate::ScriptEncoding::Utf16Le).contains(&unencodable));
+    assert_eq!(CharacterSet::ASCII7bit.encode("ä"), Err('ä'));
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn script_encoding() {
+    use crate::ScriptEncoding;
+    assert_ne!(ScriptEncoding::Auto.resolve(), ScriptEncoding::Auto);
+    assert_eq!(ScriptEncoding::Utf8.resolve(), ScriptEncoding::Utf8);
+    // the resource compiler depends on the target, not on the host of the build script
+    if cfg!(feature = "versioninfo_force_utf8") == cfg!(feature = "versioninfo_force_utf16") {
+        assert_eq!(ScriptEncoding::Auto.resolve_with(Some("gnu")), ScriptEncoding::Utf8);
+        assert_eq!(ScriptEncoding::Auto.resolve_with(None), ScriptEncoding::Utf8);
+        let msvc = if cfg!(all(feature = "embed_resource", windows)) {
+            ScriptEncoding::Utf16Le
+        } else {
+            ScriptEncoding::Utf8
+        };
+        assert_eq!(ScriptEncoding::Auto.resolve_with(Some("msvc")), msvc);
+    }
+    assert_eq!(ScriptEncoding::Utf16Le.encode("A"), [0xFF, 0xFE, b'A', 0]);
+    assert_eq!(ScriptEncoding::Utf8.encode("A"), b"A");
+
+    let rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+    let utf8 = rc.resource_script_as(ScriptEncoding::Utf8).unwrap();
+    assert!(utf8.starts_with(b"// This resource script was autogenerated"));
+    assert!(utf8.windows(24).any(|window| window == b"#pragma code_page(1252)\n"));
+    let utf16 = rc.resource_script_as(ScriptEncoding::Utf16Le).unwrap();
+    assert_eq!(utf16[..4], [0xFF, 0xFE, b'/', 0]);
+
+    crate::set_script_encoding(ScriptEncoding::Utf16Le);
+    assert_eq!(crate::link::script_encoding(), ScriptEncoding::Utf16Le);
+    crate::set_script_encoding(ScriptEncoding::Auto);
+}
+
+#[cfg(feature = "versioninfo")]
+#[test]
+fn explicit_translations() {
+    let rc = VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+    assert_eq!(rc.var_file_info, VarFileInfo::Auto);
+    assert_eq!(
+        rc.translations(),
+        [Translation::new(Language::USEnglish, CharacterSet::Multilingual)]
+    );
+
+    let rc = rc.with_translations(vec![
+        Translation::new(Language::USEnglish, CharacterSet::Multilingual),
+        Translation::NEUTRAL,
+    ]);
+    assert!(rc
+        .to_string()
+        .contains(r#"  VALUE "Translation", 0x0409, 1252, 0x0000, 1200"#));
+    assert_eq!(VersionInfo::from_rc(&rc.to_string()).unwrap(), rc);
+    assert_eq!(VersionInfo::from_bytes(&rc.to_bytes().unwrap()).unwrap(), rc);
+    assert!(!rc
+        .validate()
+        .iter()
+        .any(|diagnostic| matches!(diagnostic, Diagnostic::MissingTranslation { .. })));
+
+    // the translation list does not need string tables
+    let mut neutral = rc.clone().with_translations(vec![Translation::NEUTRAL]);
+    neutral.file_info.clear();
+    let script = neutral.to_string();
+    assert!(!script.contains("StringFileInfo"));
+    assert!(script.contains("BEGIN\n BLOCK \"VarFileInfo\""));
+    assert_eq!(VersionInfo::from_bytes(&neutral.to_bytes().unwrap()).unwrap(), neutral);
+
+    let missing = rc.with_translations(vec![Translation::NEUTRAL]);
+    assert!(missing.validate().contains(&Diagnostic::MissingTranslation {
+        lang: Language::USEnglish,
+        charset: CharacterSet::Multilingual
+    }));
+}
+
+#[cfg(feature = "manifest")]
+#[test]
+fn build_manifest() {
+    use crate::manifest::*;
+    assert_eq!(
+        Manifest::new().to_string(),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <assembly xmlns=\"urn:schemas-microsoft-com:asm.v1\" manifestVersion=\"1.0\">\n\
+         </assembly>\n"
+    );
+
+    let manifest = Manifest::new()
+        .with_ui_access(true)
+        .with_dpi_awareness(DpiAwareness::PerMonitorV2)
+        .with_long_path_aware(true)
+        .with_active_code_page(ActiveCodePage::Utf8)
+        .with_heap_type(HeapType::SegmentHeap)
+        .with_supported_os(&[SupportedOS::Windows10, SupportedOS::Windows7])
+        .with_supported_os(&SupportedOS::ALL)
+        .with_common_controls();
+    assert_eq!(manifest.execution_level, Some(ExecutionLevel::AsInvoker));
+    assert_eq!(manifest.supported_os, SupportedOS::ALL);
+
+    let xml = manifest.to_string();
+    for expected in [
+        r#"<requestedExecutionLevel level="asInvoker" uiAccess="true"/>"#,
+        r#"<supportedOS Id="{8e0f7a12-bfb3-4fe8-b9a5-48fd50a15a9a}"/>"#,
+        r#"<dpiAware xmlns="http://schemas.microsoft.com/SMI/2005/WindowsSettings">true/pm</dpiAware>"#,
+        r#"<dpiAwareness xmlns="http://schemas.microsoft.com/SMI/2016/WindowsSettings">permonitorv2,permonitor</dpiAwareness>"#,
+        r#"<longPathAware xmlns="http://schemas.microsoft.com/SMI/2016/WindowsSettings">true</longPathAware>"#,
+        r#"<activeCodePage xmlns="http://schemas.microsoft.com/SMI/2019/WindowsSettings">UTF-8</activeCodePage>"#,
+        r#"<heapType xmlns="http://schemas.microsoft.com/SMI/2020/WindowsSettings">SegmentHeap</heapType>"#,
+        r#"name="Microsoft.Windows.Common-Controls" version="6.0.0.0""#,
+    ] {
+        assert!(xml.contains(expected), "{expected} is missing in\n{xml}");
+    }
+    // every opened element is closed
+    assert_eq!(xml.matches("<trustInfo").count(), xml.matches("</trustInfo>").count());
+    assert_eq!(xml.matches("<application").count(), xml.matches("</application>").count());
+}
+
+#[cfg(feature = "manifest")]
+#[test]
+fn manifest_resource_ids() {
+    use crate::manifest::*;
+    use crate::Artifact;
+    assert_eq!(manifest_id(&Artifact::All), CREATEPROCESS_MANIFEST_RESOURCE_ID);
+    assert_eq!(manifest_id(&Artifact::bin("test")), 1);
+    assert_eq!(manifest_id(&Artifact::Cdylib), ISOLATIONAWARE_MANIFEST_RESOURCE_ID);
+    assert_eq!(
+        manifest_script(2, "manifest0.manifest"),
+        "#define RT_MANIFEST 24\n2 RT_MANIFEST \"manifest0.manifest\"\n"
+    );
+
+    let resource = ManifestStrategy::Resource;
+    reserve(&Artifact::Bins, 1, resource).unwrap();
+    assert!(reserve(&Artifact::bin("test"), 1, resource).is_err());
+    assert!(reserve(&Artifact::All, 1, resource).is_err());
+    // different resource ids do not collide
+    reserve(&Artifact::All, 2, resource).unwrap();
+    reserve(&Artifact::Cdylib, 1, resource).unwrap();
+    assert!(reserve(&Artifact::Cdylib, 2, resource).is_err());
+    LINKED_MANIFESTS.lock().unwrap().clear();
+
+    // the linker takes only one manifest per artifact, whatever its resource id
+    let linker = ManifestStrategy::Linker;
+    reserve(&Artifact::All, 1, linker).unwrap();
+    assert_eq!(
+        reserve(&Artifact::Cdylib, 2, linker),
+        Err("Only one manifest can be passed to the linker per artifact")
+    );
+    reserve(&Artifact::Cdylib, 2, resource).unwrap();
+    LINKED_MANIFESTS.lock().unwrap().clear();
+
+    // a missing manifest is an error instead of a panic, nothing is reserved for it
+    assert_eq!(
+        manifest_for("missing.manifest", &Artifact::Examples),
+        Err("The manifest can not be read, see the build script warnings")
+    );
+    assert!(LINKED_MANIFESTS.lock().unwrap().is_empty());
+}
+
+#[cfg(feature = "manifest")]
+#[test]
+fn validate_manifest() {
+    use crate::manifest::*;
+    let generated = Manifest::new()
+        .with_ui_access(false)
+        .with_dpi_awareness(DpiAwareness::PerMonitorV2)
+        .with_long_path_aware(true)
+        .with_active_code_page(ActiveCodePage::Utf8)
+        .with_heap_type(HeapType::SegmentHeap)
+        .with_supported_os(&SupportedOS::ALL)
+        .with_common_controls();
+    assert_eq!(validate(&generated.to_string()), []);
+
+    let unclosed = validate("<assembly xmlns=\"urn:schemas-microsoft-com:asm.v1\" manifestVersion=\"1.0\">\n<file>\n</assembly>");
+    assert!(matches!(unclosed[..], [ManifestError { line: 3, kind: ManifestErrorKind::Xml(_) }]));
+
+    let invalid = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <trustInfo xmlns="urn:schemas-microsoft-com:asm.v3">
+    <security>
+      <requestedPrivileges>
+        <requestedExecutionLevel level="asAdmin"/>
+        <requestedExecutionLevel level="asInvoker"/>
+      </requestedPrivileges>
+    </security>
+  </trustInfo>
+  <application xmlns="urn:schemas-microsoft-com:asm.v3">
+    <windowsSettings>
+      <dpiAware>true</dpiAware>
+      <longPathAware xmlns="http://schemas.microsoft.com/SMI/2016/WindowsSettings">yes</longPathAware>
+      <darkMode/>
+    </windowsSettings>
+  </application>
+  <file/>
+</assembly>"#;
+    let kinds = |line| {
+        validate(invalid)
+            .into_iter()
+            .filter(|error| error.line == line)
+            .map(|error| error.kind)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        kinds(6),
+        [ManifestErrorKind::InvalidValue {
+            element: "requestedExecutionLevel".into(),
+            attribute: Some("level"),
+            value: "asAdmin".into()
+        }]
+    );
+    assert_eq!(
+        kinds(7),
+        [ManifestErrorKind::DuplicateElement {
+            element: "requestedExecutionLevel".into()
+        }]
+    );
+    assert!(matches!(kinds(13)[..], [ManifestErrorKind::WrongNamespace { .. }]));
+    assert!(matches!(kinds(14)[..], [ManifestErrorKind::InvalidValue { attribute: None, .. }]));
+    assert!(matches!(kinds(15)[..], [ManifestErrorKind::UnknownElement { .. }]));
+    assert_eq!(
+        kinds(18),
+        [ManifestErrorKind::MissingAttribute {
+            element: "file".into(),
+            attribute: "name"
+        }]
+    );
+    assert_eq!(validate(invalid).len(), 6);
+    assert_eq!(
+        validate(invalid)[0].to_string(),
+        "line 6: invalid value \"asAdmin\" of `requestedExecutionLevel` attribute `level`"
+    );
+
+    assert!(kinds(15)[0].is_warning());
+    assert!(!kinds(13)[0].is_warning());
+
+    // newer elements of Microsoft are warnings, elements of other namespaces are errors
+    let newer = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <msix xmlns="urn:schemas-microsoft-com:msix.v1" publisher="CN=Example" packageName="Example" applicationId="App"/>
+  <application xmlns="urn:schemas-microsoft-com:asm.v3">
+    <windowsSettings>
+      <highResolutionScrollingAware xmlns="http://schemas.microsoft.com/SMI/2013/WindowsSettings">true</highResolutionScrollingAware>
+      <supportedArchitectures xmlns="http://schemas.microsoft.com/SMI/2024/WindowsSettings">amd64 arm64</supportedArchitectures>
+      <futureSetting xmlns="http://schemas.microsoft.com/SMI/2030/WindowsSettings">true</futureSetting>
+    </windowsSettings>
+  </application>
+</assembly>"#;
+    let errors = validate(newer);
+    assert_eq!(
+        errors,
+        [ManifestError {
+            line: 8,
+            kind: ManifestErrorKind::UnknownElement {
+                element: "futureSetting".into(),
+                namespace: "http://schemas.microsoft.com/SMI/2030/WindowsSettings".into()
+            }
+        }]
+    );
+    assert!(errors[0].kind.is_warning());
+    assert!(MergedManifest::new().with_xml(newer).unwrap().to_string().contains("<futureSetting"));
+    let foreign = newer.replace("http://schemas.microsoft.com/SMI/2030/WindowsSettings", "urn:example");
+    assert!(!validate(&foreign)[0].kind.is_warning());
+    assert!(MergedManifest::new().with_xml(&foreign).is_err());
+
+    // elements are identified by their schema, not by their name or their exact namespace
+    let mixed = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <trustInfo xmlns="urn:schemas-microsoft-com:asm.v2"/>
+  <trustInfo xmlns="urn:schemas-microsoft-com:asm.v3"/>
+  <application xmlns="urn:schemas-microsoft-com:asm.v3">
+    <supportedOS xmlns="urn:schemas-microsoft-com:compatibility.v1" Id="{8e0f7a12-bfb3-4fe8-b9a5-48fd50a15a9a}"/>
+  </application>
+</assembly>"#;
+    assert_eq!(
+        validate(mixed),
+        [
+            ManifestError {
+                line: 4,
+                kind: ManifestErrorKind::DuplicateElement {
+                    element: "trustInfo".into()
+                }
+            },
+            ManifestError {
+                line: 6,
+                kind: ManifestErrorKind::UnexpectedElement {
+                    element: "supportedOS".into(),
+                    parent: "application".into()
+                }
+            },
+        ]
+    );
+}
+
+#[cfg(feature = "manifest")]
+#[test]
+fn merge_manifests() {
+    use crate::manifest::*;
+    let dpi = Manifest::new()
+        .with_dpi_awareness(DpiAwareness::PerMonitorV2)
+        .with_supported_os(&[SupportedOS::Windows10]);
+    let controls = Manifest::new()
+        .with_common_controls()
+        .with_supported_os(&SupportedOS::ALL);
+    let merged = MergedManifest::new()
+        .with_manifest(&dpi)
+        .unwrap()
+        .with_manifest(&controls)
+        .unwrap()
+        // adding the same settings again changes nothing
+        .with_manifest(&controls)
+        .unwrap();
+    let xml = merged.to_string();
+    assert_eq!(validate(&xml), []);
+    assert_eq!(xml.matches("<supportedOS").count(), 4);
+    assert_eq!(xml.matches("<dependency>").count(), 1);
+    assert_eq!(xml.matches("<windowsSettings>").count(), 1);
+    assert!(xml.contains(r#"<dpiAwareness xmlns="http://schemas.microsoft.com/SMI/2016/WindowsSettings">permonitorv2,permonitor</dpiAwareness>"#));
+
+    // settings of fragments are combined into the existing elements
+    let long_paths = r#"<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <application xmlns="urn:schemas-microsoft-com:asm.v3">
+    <windowsSettings>
+      <longPathAware xmlns="http://schemas.microsoft.com/SMI/2016/WindowsSettings">true</longPathAware>
+    </windowsSettings>
+  </application>
+</assembly>"#;
+    let merged = merged.with_xml(long_paths).unwrap();
+    let xml = merged.to_string();
+    assert_eq!(xml.matches("<windowsSettings>").count(), 1);
+    assert!(xml.contains("<longPathAware"));
+
+    let conflict = merged
+        .clone()
+        .with_xml(&long_paths.replace(">true<", ">false<"))
+        .unwrap_err();
+    assert_eq!(
+        conflict,
+        [ManifestError {
+            line: 4,
+            kind: ManifestErrorKind::Conflict {
+                element: "longPathAware".into(),
+                attribute: None,
+                first: "true".into(),
+                second: "false".into()
+            }
+        }]
+    );
+    let elevated = Manifest::new().with_execution_level(ExecutionLevel::RequireAdministrator);
+    let invoker = Manifest::new().with_execution_level(ExecutionLevel::AsInvoker);
+    let conflict = MergedManifest::new()
+        .with_manifest(&elevated)
+        .unwrap()
+        .with_manifest(&invoker)
+        .unwrap_err();
+    assert!(matches!(
+        &conflict[0].kind,
+        ManifestErrorKind::Conflict { attribute: Some(attribute), .. } if attribute == "level"
+    ));
+    // `trustInfo` in the older `asm.v2` namespace is the same element as the one of the builder
+    let elevated_v2 = r#"<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <trustInfo xmlns="urn:schemas-microsoft-com:asm.v2">
+    <security>
+      <requestedPrivileges>
+        <requestedExecutionLevel level="requireAdministrator" uiAccess="false"/>
+      </requestedPrivileges>
+    </security>
+  </trustInfo>
+</assembly>"#;
+    let conflict = MergedManifest::new()
+        .with_xml(elevated_v2)
+        .unwrap()
+        .with_manifest(&invoker)
+        .unwrap_err();
+    assert!(matches!(
+        &conflict[..],
+        [ManifestError {
+            kind: ManifestErrorKind::Conflict { attribute: Some(attribute), first, second, .. },
+            ..
+        }] if attribute == "level" && first == "requireAdministrator" && second == "asInvoker"
+    ));
+    assert!(merged.with_xml("<assembly/>").is_err());
+}
+
+#[cfg(feature = "manifest")]
+#[test]
+fn manifest_template() {
+    use crate::manifest::*;
+    std::env::set_var("WINDOWS_EXE_INFO_TEST_COMPANY", "Example & Co");
+    assert_eq!(
+        expand_template(r#"<assemblyIdentity name="${WINDOWS_EXE_INFO_TEST_COMPANY}.${CARGO_PKG_NAME}" version="${ASSEMBLY_VERSION}"/>"#),
+        Ok(format!(
+            r#"<assemblyIdentity name="Example &amp; Co.windows_exe_info" version="{}.{}.{}.0"/>"#,
+            env!("CARGO_PKG_VERSION_MAJOR"),
+            env!("CARGO_PKG_VERSION_MINOR"),
+            env!("CARGO_PKG_VERSION_PATCH")
+        ))
+    );
+    assert_eq!(expand_template("no placeholders $ {}"), Ok("no placeholders $ {}".into()));
+    assert!(expand_template("${WINDOWS_EXE_INFO_TEST_UNSET}").is_err());
+    assert!(expand_template("${CARGO_PKG_NAME").is_err());
+}
+
+#[cfg(feature = "manifest")]
+#[test]
+fn manifest_assembly_identity() {
+    use crate::manifest::*;
+    let identity = AssemblyIdentity {
+        name: "Example.Tool".into(),
+        version: [1, 2, 3, 4],
+        processor_architecture: Some(ProcessorArchitecture::Arm64),
+    };
+    let xml = Manifest::new()
+        .with_execution_level(ExecutionLevel::AsInvoker)
+        .with_assembly_identity(identity)
+        .to_string();
+    assert_eq!(validate(&xml), []);
+    // the identity is the first element of the assembly
+    assert_eq!(
+        xml.lines().nth(2),
+        Some(r#"  <assemblyIdentity type="win32" name="Example.Tool" version="1.2.3.4" processorArchitecture="arm64"/>"#)
+    );
+
+    let identity = AssemblyIdentity::from_cargo_env();
+    assert_eq!(identity.name, "windows_exe_info");
+    assert_eq!(identity.version[1], env!("CARGO_PKG_VERSION_MINOR").parse::<u16>().unwrap());
+
+    #[cfg(feature = "versioninfo")]
+    {
+        let version_info = crate::versioninfo::VersionInfo::from_rc(FORMATTED_VERSIONINFO).unwrap();
+        let identity = AssemblyIdentity::from_version_info(&version_info);
+        assert_eq!(identity.name, "6.7.8.9RC0");
+        assert_eq!(identity.version, [0, 1, 2, 3]);
+    }
+}
+
+#[cfg(feature = "manifest")]
+#[test]
+fn manifest_com() {
+    use crate::manifest::*;
+    const CLSID: &str = "{5c1a6c2e-2a8b-4c33-9d6e-0d4c3c8a9f10}";
+    const TLBID: &str = "{8d3f3c1b-6f2e-4b6a-a1d4-57e2b8f4c0a2}";
+    let class = ComClass {
+        threading_model: Some(ThreadingModel::Apartment),
+        progid: Some("Example.Server".into()),
+        tlbid: Some(TLBID.into()),
+        ..ComClass::new(CLSID)
+    };
+    let typelib = TypeLib {
+        tlbid: TLBID.into(),
+        version: "1.0".into(),
+        help_dir: String::new(),
+    };
+    let xml = Manifest::new()
+        .with_assembly_identity(AssemblyIdentity {
+            name: "Example.Server".into(),
+            version: [1, 0, 0, 0],
+            processor_architecture: None,
+        })
+        .with_file(AssemblyFile::new("server.dll").with_com_class(class).with_typelib(typelib))
+        .with_com_interface(ComInterface {
+            iid: "{0e3b5f5a-7c8d-4e3f-9a1b-2c4d6e8f0a1b}".into(),
+            name: "IExample".into(),
+            tlbid: Some(TLBID.into()),
+            proxy_stub_clsid32: Some(TYPELIB_MARSHALER.into()),
+        })
+        .with_dependency(AssemblyIdentity {
+            name: "Example.Private".into(),
+            version: [2, 0, 0, 0],
+            processor_architecture: Some(ProcessorArchitecture::Amd64),
+        })
+        .to_string();
+    assert_eq!(validate(&xml), []);
+    assert!(xml.contains(&format!(
+        r#"<comClass clsid="{CLSID}" threadingModel="Apartment" progid="Example.Server" tlbid="{TLBID}"/>"#
+    )));
+    assert!(xml.contains(&format!(r#"<typelib tlbid="{TLBID}" version="1.0" helpdir=""/>"#)));
+    assert!(xml.contains(
+        r#"      <assemblyIdentity type="win32" name="Example.Private" version="2.0.0.0" processorArchitecture="amd64"/>"#
+    ));
+
+    // the class id is required to be a GUID in braces
+    let xml = Manifest::new()
+        .with_file(AssemblyFile::new("server.dll").with_com_class(ComClass::new("Example.Server")))
+        .to_string();
+    assert!(matches!(
+        validate(&xml).as_slice(),
+        [ManifestError { kind: ManifestErrorKind::InvalidValue { attribute: Some("clsid"), .. }, .. }]
+    ));
+}
+
+#[cfg(feature = "manifest")]
+#[test]
+fn manifest_linker_strategy() {
+    use crate::manifest::*;
+    use crate::Artifact;
+    assert_eq!(ManifestStrategy::Resource.resolve_for(&Artifact::Bins), ManifestStrategy::Resource);
+    assert_eq!(ManifestStrategy::Linker.resolve_for(&Artifact::Cdylib), ManifestStrategy::Linker);
+    // `Auto` passes only the manifests of the binaries of msvc targets to the linker
+    let auto = |target_env: Option<&str>, artifact: &Artifact| ManifestStrategy::Auto.resolve_with(target_env, artifact);
+    assert_eq!(auto(Some("msvc"), &Artifact::Bins), ManifestStrategy::Linker);
+    assert_eq!(auto(Some("msvc"), &Artifact::bin("cli")), ManifestStrategy::Linker);
+    assert_eq!(auto(Some("msvc"), &Artifact::All), ManifestStrategy::Resource);
+    assert_eq!(auto(Some("msvc"), &Artifact::Cdylib), ManifestStrategy::Resource);
+    assert_eq!(auto(Some("gnu"), &Artifact::Bins), ManifestStrategy::Resource);
+    assert_eq!(auto(None, &Artifact::Bins), ManifestStrategy::Resource);
+
+    let xml = Manifest::new().with_dpi_awareness(DpiAwareness::PerMonitorV2).to_string();
+    assert_eq!(
+        manifest_linker_args(1, "C:/out dir/manifest0.manifest", &xml),
+        ["/MANIFEST:EMBED,ID=1", "/MANIFESTINPUT:C:/out dir/manifest0.manifest"]
+    );
+    // the execution level of the manifest replaces the one of the linker
+    let xml = Manifest::new().with_execution_level(ExecutionLevel::RequireAdministrator).to_string();
+    assert_eq!(
+        manifest_linker_args(2, "manifest1.manifest", &xml),
+        ["/MANIFEST:EMBED,ID=2", "/MANIFESTINPUT:manifest1.manifest", "/MANIFESTUAC:NO"]
+    );
+}
+
+#[cfg(feature = "string_table")]
+#[test]
+fn string_table() {
+    use crate::string_table::*;
+    use crate::versioninfo::{Language, Severity};
+    let table = StringTable::new()
+        .with_strings(Language::USEnglish, [(1, "Open"), (15, "Say \"hi\"\n"), (16, "Next block")])
+        .with_strings(Language::German, [(1, "Öffnen")])
+        .with_id("IDS_OPEN", 1)
+        .with_id("IDS_NEXT", 16);
+    assert_eq!(
+        table.to_string(),
+        "// This resource script was autogenerated
+// Do not change manually!!!
+LANGUAGE 0x9, 0x1
+STRINGTABLE
+BEGIN
+ 1, \"Open\"
+ 15, \"Say \"\"hi\"\"\\n\"
+END
+STRINGTABLE
+BEGIN
+ 16, \"Next block\"
+END
+LANGUAGE 0x7, 0x1
+STRINGTABLE
+BEGIN
+ 1, \"Öffnen\"
+END
+"
+    );
+    assert_eq!(
+        table.ids_module(),
+        "// This file was autogenerated\n// Do not change manually!!!\npub const IDS_NEXT: u32 = 16;\npub const IDS_OPEN: u32 = 1;\n"
+    );
+    assert_eq!(
+        table.validate(),
+        [
+            Diagnostic::MissingString { lang: Language::German, id: 15 },
+            Diagnostic::MissingString { lang: Language::German, id: 16 },
+        ]
+    );
+
+    let diagnostics = table.with_id("2nd", 2).validate();
+    assert!(diagnostics.contains(&Diagnostic::InvalidName { name: "2nd".into(), id: 2 }));
+    assert!(diagnostics.contains(&Diagnostic::MissingId { name: "2nd".into(), id: 2 }));
+    assert!(diagnostics.iter().any(|diagnostic| diagnostic.severity() == Severity::Error));
+
+    // keywords would not compile as names of constants
+    let open = StringTable::new().with_strings(Language::USEnglish, [(1, "Open")]);
+    for keyword in ["type", "self", "Self", "crate", "union", "async", "try"] {
+        assert_eq!(
+            open.clone().with_id(keyword, 1).validate(),
+            [Diagnostic::InvalidName { name: keyword.into(), id: 1 }]
+        );
+    }
+    assert_eq!(open.with_id("TYPE", 1).validate(), []);
+}
+
+#[cfg(feature = "raw")]
+#[test]
+fn raw_resources() {
+    use crate::raw::*;
+    use crate::versioninfo::Language;
+    let license = RawResource::file("LICENSE", "my docs/lizenz ä.txt")
+        .with_type(ResourceType::Named("TEXT".into()))
+        .with_language(Language::German)
+        .with_constant("LICENSE");
+    // the data is referenced by its generated name in `OUT_DIR`
+    assert_eq!(license.script("raw0-0.bin"), "LANGUAGE 0x7, 0x1\nLICENSE TEXT \"raw0-0.bin\"\n");
+    let config = RawResource::bytes(200, vec![1, 2, 3]).with_constant("CONFIG");
+    assert_eq!(config.script("raw0-1.bin"), "LANGUAGE 0x0, 0x0\n200 RCDATA \"raw0-1.bin\"\n");
+    assert_eq!(
+        RawResource::bytes(7, "").with_type(ResourceType::Numeric(300)).script("raw0-2.bin"),
+        "LANGUAGE 0x0, 0x0\n7 300 \"raw0-2.bin\"\n"
+    );
+
+    let resources = RawResources::new().with(license).with(config.clone());
+    assert_eq!(resources.validate(), []);
+    assert_eq!(
+        resources.ids_module(),
+        "// This file was autogenerated\n// Do not change manually!!!\npub const LICENSE: &str = \"LICENSE\";\npub const CONFIG: u16 = 200;\n"
+    );
+
+    let invalid = RawResources::new()
+        .with(config.clone())
+        .with(config)
+        .with(RawResource::bytes("my id", "").with_type(ResourceType::Numeric(3)))
+        .with(RawResource::bytes("ICON", "").with_constant("2x"))
+        .with(RawResource::bytes(201, "").with_constant("type"));
+    assert_eq!(
+        invalid.validate(),
+        [
+            RawError::DuplicateConstant("CONFIG".into()),
+            RawError::Duplicate {
+                resource_type: ResourceType::RcData,
+                id: ResourceId::Numeric(200),
+                lang: Language::Neutral,
+            },
+            RawError::ReservedType(3),
+            RawError::InvalidName("my id".into()),
+            RawError::InvalidName("ICON".into()),
+            RawError::InvalidConstant("2x".into()),
+            RawError::InvalidConstant("type".into()),
+        ]
+    );
+
+    // Windows compares names case insensitive
+    let license = RawResource::bytes("LICENSE", "MIT").with_type(ResourceType::Named("TEXT".into()));
+    let lowercase = RawResource::bytes("License", "MIT").with_type(ResourceType::Named("text".into()));
+    assert_eq!(
+        RawResources::new().with(license).with(lowercase).validate(),
+        [RawError::Duplicate {
+            resource_type: ResourceType::Named("text".into()),
+            id: ResourceId::Named("License".into()),
+            lang: Language::Neutral,
+        }]
+    );
+
+    // a missing file is an error instead of a panic, nothing is reserved for it
+    let missing = RawResources::new().with(RawResource::file(300, "missing.bin"));
+    assert_eq!(
+        missing.link_for(&crate::Artifact::Examples),
+        Err("A raw resource file can not be read, see the build script warnings")
+    );
+    assert!(LINKED_RAW_RESOURCES.lock().unwrap().is_empty());
+
+    // files with spaces and non-ASCII characters in their path are copied to generated names
+    let dir = camino::Utf8PathBuf::try_from(std::env::temp_dir().join("windows_exe_info_raw")).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    let (docs, out_dir) = (dir.join("my docs"), dir.join("out"));
+    std::fs::create_dir_all(&docs).unwrap();
+    std::fs::create_dir_all(&out_dir).unwrap();
+    std::fs::write(docs.join("lizenz ä.txt"), "MIT Lizenz").unwrap();
+    let resources = RawResources::new()
+        .with(RawResource::file("LICENSE", docs.join("lizenz ä.txt")))
+        .with(RawResource::bytes(200, vec![1, 2, 3]));
+    let data = resources.read_data().unwrap();
+    let script = resources.write_files(&out_dir, 5, &data, crate::ScriptEncoding::Utf8);
+    assert_eq!(script, out_dir.join("raw5.rc"));
+    assert_eq!(
+        std::fs::read_to_string(&script).unwrap(),
+        "LANGUAGE 0x0, 0x0\nLICENSE RCDATA \"raw5-0.bin\"\nLANGUAGE 0x0, 0x0\n200 RCDATA \"raw5-1.bin\"\n"
+    );
+    assert_eq!(std::fs::read(out_dir.join("raw5-0.bin")).unwrap(), b"MIT Lizenz");
+    assert_eq!(std::fs::read(out_dir.join("raw5-1.bin")).unwrap(), [1, 2, 3]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
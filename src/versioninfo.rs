@@ -1,15 +1,37 @@
 //! Structs for adding version information to an executable
+use std::collections::BTreeMap;
 use std::env::var;
 use std::fmt::Write as FmtWrite;
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
-pub(crate) static HAS_LINKED_VERSION_INFO: AtomicBool = AtomicBool::new(false);
+use crate::{Artifact, ScriptEncoding};
+
+mod binary;
+mod encoding;
+#[cfg(feature = "git")]
+pub(crate) mod git;
+#[cfg(feature = "locale")]
+mod locale;
+pub(crate) mod package;
+mod parse;
+mod validate;
+pub use binary::{DecodeError, EncodeError};
+#[cfg(feature = "git")]
+pub use git::GitInfo;
+#[cfg(feature = "locale")]
+pub use locale::LocaleError;
+pub use package::{CompanySource, CopyrightSource};
+pub use parse::ParseError;
+pub use validate::{Diagnostic, Severity};
+
+/// the artifacts a versioninfo has been linked to
+pub(crate) static LINKED_VERSION_INFO: Mutex<Vec<Artifact>> = Mutex::new(Vec::new());
 
 /// The main wrapper struct.
 /// Implements custom formatting converting it into an rc script.
-/// Only one versioninfo struct can be used per executable.
+/// Only one versioninfo struct can be used per artifact.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VersionInfo {
     pub file_version: Version,
     pub product_version: Version,
@@ -18,25 +40,41 @@ pub struct VersionInfo {
     pub file_os: FileOS,
     pub file_type: FileType,
     pub file_info: Vec<FileInfo>,
+    /// the translations listed in the `VarFileInfo` block
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub var_file_info: VarFileInfo,
 }
 
+/// `{:#}` writes numeric literals instead of the `winver.h` macros,
+/// so the script compiles without `#include<winver.h>`
 impl core::fmt::Display for VersionInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut result = String::new();
         writeln!(result, "// This resource script was autogenerated")?;
         writeln!(result, "// Do not change manually!!!")?;
-        writeln!(result, "#include<winver.h>")?;
-        writeln!(result, "VS_VERSION_INFO VERSIONINFO")?;
-        writeln!(result, "FILEVERSION     {}", self.file_version)?;
-        writeln!(result, "PRODUCTVERSION  {}", self.product_version)?;
-        writeln!(result, "FILEFLAGSMASK   {}", self.file_flag_mask)?;
-        writeln!(result, "FILEFLAGS       {}", self.file_flags)?;
-        writeln!(result, "FILEOS          {}", self.file_os)?;
-        writeln!(result, "FILETYPE        {}", self.file_type)?;
-        writeln!(result, "FILESUBTYPE     {}", self.file_type.get_subvalue())?;
+        if f.alternate() {
+            writeln!(result, "1 VERSIONINFO")?;
+            writeln!(result, "FILEVERSION     {}", self.file_version)?;
+            writeln!(result, "PRODUCTVERSION  {}", self.product_version)?;
+            writeln!(result, "FILEFLAGSMASK   {:#}", self.file_flag_mask)?;
+            writeln!(result, "FILEFLAGS       {:#}", self.file_flags)?;
+            writeln!(result, "FILEOS          {:#}", self.file_os)?;
+            writeln!(result, "FILETYPE        {:#}", self.file_type)?;
+            writeln!(result, "FILESUBTYPE     {:#X}", self.file_type.subtype_value())?;
+        } else {
+            writeln!(result, "#include<winver.h>")?;
+            writeln!(result, "VS_VERSION_INFO VERSIONINFO")?;
+            writeln!(result, "FILEVERSION     {}", self.file_version)?;
+            writeln!(result, "PRODUCTVERSION  {}", self.product_version)?;
+            writeln!(result, "FILEFLAGSMASK   {}", self.file_flag_mask)?;
+            writeln!(result, "FILEFLAGS       {}", self.file_flags)?;
+            writeln!(result, "FILEOS          {}", self.file_os)?;
+            writeln!(result, "FILETYPE        {}", self.file_type)?;
+            writeln!(result, "FILESUBTYPE     {}", self.file_type.get_subvalue())?;
+        }
 
+        writeln!(result, "BEGIN")?;
         if !self.file_info.is_empty() {
-            writeln!(result, "BEGIN")?;
             writeln!(result, r#" BLOCK "StringFileInfo""#)?;
             writeln!(result, " BEGIN")?;
             for file_info in &self.file_info {
@@ -47,69 +85,25 @@ impl core::fmt::Display for VersionInfo {
                     file_info.charset.to_hex()
                 )?;
                 writeln!(result, "  BEGIN")?;
-                writeln!(
-                    result,
-                    r#"   VALUE "CompanyName", {}"#,
-                    file_info.company_name
-                )?;
-                writeln!(
-                    result,
-                    r#"   VALUE "FileDescription", {}"#,
-                    file_info.file_description
-                )?;
-                writeln!(
-                    result,
-                    r#"   VALUE "FileVersion", {}"#,
-                    file_info.file_version
-                )?;
-                writeln!(
-                    result,
-                    r#"   VALUE "InternalName", {}"#,
-                    file_info.internal_name
-                )?;
-                writeln!(
-                    result,
-                    r#"   VALUE "OriginalFilename", {}"#,
-                    file_info.original_filename
-                )?;
-                writeln!(
-                    result,
-                    r#"   VALUE "ProductName", {}"#,
-                    file_info.product_name
-                )?;
-                writeln!(
-                    result,
-                    r#"   VALUE "ProductVersion", {}"#,
-                    file_info.product_version
-                )?;
-                if let Some(t) = &file_info.comment {
-                    writeln!(result, r#"   VALUE "Comments", {t}"#)?;
-                }
-                if let Some(t) = &file_info.legal_copyright {
-                    writeln!(result, r#"   VALUE "LegalCopyright", {t}"#)?;
-                }
-                if let Some(t) = &file_info.legal_trademarks {
-                    writeln!(result, r#"   VALUE "LegalTrademarks", {t}"#)?;
-                }
-                if let Some(t) = &file_info.private_build {
-                    writeln!(result, r#"   VALUE "PrivateBuild", {t}"#)?;
-                }
-                if let Some(t) = &file_info.special_build {
-                    writeln!(result, r#"   VALUE "SpecialBuild", {t}"#)?;
+                for (key, t) in file_info.values() {
+                    writeln!(result, r#"   VALUE "{}", {t}"#, escape(key))?;
                 }
                 writeln!(result, "  END")?;
             }
             writeln!(result, " END")?;
             writeln!(result)?;
+        }
+        let translations = self.translations();
+        if !translations.is_empty() {
             writeln!(result, r#" BLOCK "VarFileInfo""#)?;
             writeln!(result, " BEGIN")?;
             write!(result, r#"  VALUE "Translation""#)?;
-            for file_info in &self.file_info {
+            for translation in &translations {
                 write!(
                     result,
                     ", 0x{}, {}",
-                    file_info.lang.to_hex(),
-                    file_info.charset.to_decimal()
+                    translation.lang.to_hex(),
+                    translation.charset.to_decimal()
                 )?;
             }
             writeln!(result)?;
@@ -121,47 +115,61 @@ impl core::fmt::Display for VersionInfo {
 }
 impl VersionInfo {
     /// Writes the content of the struct into a file and tries to compile and link it
-    /// panics if it is invoked more than once
+    /// to the binaries. Fails if a versioninfo was already linked.
+    /// The struct is validated first, warnings are shown as build script warnings
+    /// and errors prevent linking
     pub fn link(&self) -> Result<(), &str> {
-        if HAS_LINKED_VERSION_INFO.load(Ordering::Relaxed) {
-            return Err("Only one versioninfo can be linked");
+        self.link_for(&Artifact::Bins)
+    }
+
+    /// Like `link`, but links the version information only to the given artifact.
+    /// Every artifact can get its own versioninfo, linking a second one to the same artifact
+    /// (e.g. `Artifact::All` and `Artifact::Bin`) fails.
+    pub fn link_for(&self, artifact: &Artifact) -> Result<(), &str> {
+        if LINKED_VERSION_INFO
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|linked| linked.overlaps(artifact))
+        {
+            return Err("Only one versioninfo can be linked per artifact");
+        }
+        let diagnostics = self.validate();
+        for diagnostic in &diagnostics {
+            println!("cargo:warning={diagnostic}");
         }
-        let output_dir = var("OUT_DIR").unwrap();
-        let buildres_file = format!("{output_dir}/info.rc");
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity() == Severity::Error)
         {
-            let mut file = OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(&buildres_file)
-                .unwrap();
-            let resource_script_content = self.to_string();
-
-            let target = std::env::var("TARGET").expect("Can not read rust target");
-            #[cfg(all(feature = "versioninfo_force_utf8", feature = "versioninfo_force_utf16"))]
-            panic!("Versioninfo must either be utf8 or utf16, not both");
-
-            if (target.ends_with("msvc") || cfg!(feature = "versioninfo_force_utf16")) && !cfg!(feature = "versioninfo_force_utf8") {
-                // write UTF16LE as we expect to use microsoft winres
-
-                // no buffering -> file is small ...
-                file.write_all(&[0xFF, 0xFE]).unwrap(); // UTF16LE-BOM
-                for utf16 in resource_script_content.encode_utf16() {
-                    file.write_all(&utf16.to_le_bytes()).unwrap();
-                }
-            } else if (target.ends_with("gnu") || cfg!(feature = "versioninfo_force_utf8")) && !cfg!(feature = "versioninfo_force_utf16") {
-                // write UTF8 as we expect to use mingw windres
-                file.write_all(resource_script_content.as_bytes()).unwrap();
-            } else if target.contains("windows") {
-                panic!("Can not infer whether Versioninfo should be utf8 or utf16");
-            }
-        } // implicit close file
+            return Err("Invalid versioninfo, see the build script warnings");
+        }
+        let buildres_file = super::link::write_out_file(
+            &format!("info{}.rc", artifact.file_suffix()),
+            &self
+                .resource_script()
+                .map_err(|_| "Versioninfo strings can not be encoded in their character set, see the build script warnings")?,
+        );
 
-        super::link::link(buildres_file);
-        HAS_LINKED_VERSION_INFO.store(true, Ordering::Relaxed);
+        super::link::link_for(buildres_file, artifact);
+        LINKED_VERSION_INFO.lock().unwrap().push(artifact.clone());
         Ok(())
     }
 
+    /// the resource script written by `link_for`, encoded using the selected `ScriptEncoding`.
+    /// Contains neither paths nor timestamps, so the same struct always results in the same bytes
+    pub(crate) fn resource_script(&self) -> Result<Vec<u8>, Diagnostic> {
+        self.resource_script_as(super::link::script_encoding())
+    }
+
+    pub(crate) fn resource_script_as(&self, encoding: ScriptEncoding) -> Result<Vec<u8>, Diagnostic> {
+        match encoding.resolve() {
+            // each string table is encoded in the codepage of its character set
+            ScriptEncoding::Utf8 => self.encoded_script(),
+            encoding => Ok(encoding.encode(&format!("{self:#}"))),
+        }
+    }
+
     /// creates the `VersionInfo` struct from cargo environment variables.
     /// sets the `FileInfo` Language to English and without the optional fields
     /// `comment`, `company_name`, `legal_copyright` and `legal_trademarks`
@@ -169,6 +177,44 @@ impl VersionInfo {
         Self::from_cargo_env_ex(None, None, None, None)
     }
 
+    /// creates the `VersionInfo` struct from cargo environment variables
+    /// for the given artifact, see `with_artifact`
+    pub fn from_cargo_env_for(artifact: &Artifact) -> Self {
+        Self::from_cargo_env().with_artifact(artifact)
+    }
+
+    /// Sets the file type and the file names to match the artifact:
+    /// a cdylib becomes a `Dll` named like the library (the package name with `-` replaced by `_`),
+    /// a binary is named like the binary. Other artifacts are left unchanged
+    pub fn with_artifact(mut self, artifact: &Artifact) -> Self {
+        let (file_type, name, extension) = match artifact {
+            Artifact::Cdylib => (
+                FileType::Dll,
+                var("CARGO_PKG_NAME").unwrap_or_default().replace('-', "_"),
+                "dll",
+            ),
+            Artifact::Bin(bin) => (FileType::App, bin.clone(), "exe"),
+            _ => return self,
+        };
+        self.file_type = file_type;
+        for file_info in &mut self.file_info {
+            file_info.internal_name = name.as_str().into();
+            file_info.original_filename = format!("{name}.{extension}").into();
+        }
+        self
+    }
+
+    /// creates the `VersionInfo` struct from cargo environment variables,
+    /// deriving `company_name` and `legal_copyright` from the given sources
+    pub fn from_cargo_env_with(company_name: &CompanySource, legal_copyright: &CopyrightSource) -> Self {
+        Self::from_cargo_env_ex(
+            None,
+            company_name.resolve().as_deref(),
+            legal_copyright.resolve().as_deref(),
+            None,
+        )
+    }
+
     /// creates the `VersionInfo` struct from cargo environment variables.
     /// sets the `FileInfo` Language to English including the optional fields
     /// `comment`, `company_name`, `legal_copyright` and `legal_trademarks`
@@ -197,6 +243,11 @@ impl VersionInfo {
                 .and_then(|pre| pre.parse().ok())
                 .unwrap_or_default(),
         );
+        let name = var("CARGO_PKG_NAME").unwrap_or_default();
+        let description = match var("CARGO_PKG_DESCRIPTION") {
+            Ok(description) if !description.is_empty() => description,
+            _ => name.clone(),
+        };
         Self {
             file_version: version,
             product_version: version,
@@ -216,7 +267,7 @@ impl VersionInfo {
                 charset: CharacterSet::Multilingual,
                 comment: comment.map(<&str>::into),
                 company_name: company_name.map(<&str>::into).unwrap_or_default(),
-                file_description: var("CARGO_PKG_DESCRIPTION").unwrap_or_default().into(),
+                file_description: description.into(),
                 file_version: var("CARGO_PKG_VERSION").unwrap_or_default().into(),
                 internal_name: var("CARGO_PKG_NAME").unwrap_or_default().into(),
                 legal_copyright: legal_copyright.map(<&str>::into),
@@ -227,9 +278,29 @@ impl VersionInfo {
                 product_version: var("CARGO_PKG_VERSION").unwrap_or_default().into(),
                 private_build: None,
                 special_build: None,
+                custom: BTreeMap::new(),
             }],
+            var_file_info: VarFileInfo::Auto,
         }
     }
+
+    /// the translations written to the `VarFileInfo` block, see [`VarFileInfo`]
+    pub fn translations(&self) -> Vec<Translation> {
+        match &self.var_file_info {
+            VarFileInfo::Auto => self
+                .file_info
+                .iter()
+                .map(|file_info| Translation::new(file_info.lang, file_info.charset))
+                .collect(),
+            VarFileInfo::Explicit(translations) => translations.clone(),
+        }
+    }
+
+    /// sets the translations of the `VarFileInfo` block independent of the string tables
+    pub fn with_translations(mut self, translations: Vec<Translation>) -> Self {
+        self.var_file_info = VarFileInfo::Explicit(translations);
+        self
+    }
 }
 
 impl Default for VersionInfo {
@@ -248,6 +319,8 @@ pub fn link_cargo_env() {
 /// Representation of the STRINGFILEINFO block in a versioninfo struct.
 /// Can be used multiple times in the main VERSIONINFO block
 /// for different languages
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileInfo {
     pub lang: Language,
     pub charset: CharacterSet,
@@ -275,10 +348,147 @@ pub struct FileInfo {
     pub private_build: Option<RCString>,
     ///Text that specifies how this version of the file differs from the standard version—for example, "Private build for TESTER1 solving mouse problems on M250 and M250E computers". This string should be present only if VS_FF_SPECIALBUILD is specified in the fileflags parameter of the root block.
     pub special_build: Option<RCString>,
-    //pub custom: HashMap<String, RCString>,
+    ///Additional non standard strings, written after the standard ones in key order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub custom: BTreeMap<String, RCString>,
+}
+
+impl FileInfo {
+    /// creates a `FileInfo` with all strings empty
+    pub(crate) fn empty(lang: Language, charset: CharacterSet) -> Self {
+        Self {
+            lang,
+            charset,
+            comment: None,
+            company_name: RCString::default(),
+            file_description: RCString::default(),
+            file_version: RCString::default(),
+            internal_name: RCString::default(),
+            legal_copyright: None,
+            legal_trademarks: None,
+            original_filename: RCString::default(),
+            product_name: RCString::default(),
+            product_version: RCString::default(),
+            private_build: None,
+            special_build: None,
+            custom: BTreeMap::new(),
+        }
+    }
+
+    /// all strings of the block as key value pairs in the order they are written
+    pub(crate) fn values(&self) -> Vec<(&str, &RCString)> {
+        let mut values = vec![
+            ("CompanyName", &self.company_name),
+            ("FileDescription", &self.file_description),
+            ("FileVersion", &self.file_version),
+            ("InternalName", &self.internal_name),
+            ("OriginalFilename", &self.original_filename),
+            ("ProductName", &self.product_name),
+            ("ProductVersion", &self.product_version),
+        ];
+        let optional = [
+            ("Comments", &self.comment),
+            ("LegalCopyright", &self.legal_copyright),
+            ("LegalTrademarks", &self.legal_trademarks),
+            ("PrivateBuild", &self.private_build),
+            ("SpecialBuild", &self.special_build),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                values.push((key, value));
+            }
+        }
+        values.extend(self.custom.iter().map(|(key, value)| (key.as_str(), value)));
+        values
+    }
+
+    /// sets the string `key`, unknown keys are added to `custom`
+    pub(crate) fn insert(&mut self, key: String, value: RCString) {
+        match key.as_str() {
+            "Comments" => self.comment = Some(value),
+            "CompanyName" => self.company_name = value,
+            "FileDescription" => self.file_description = value,
+            "FileVersion" => self.file_version = value,
+            "InternalName" => self.internal_name = value,
+            "LegalCopyright" => self.legal_copyright = Some(value),
+            "LegalTrademarks" => self.legal_trademarks = Some(value),
+            "OriginalFilename" => self.original_filename = value,
+            "ProductName" => self.product_name = value,
+            "ProductVersion" => self.product_version = value,
+            "PrivateBuild" => self.private_build = Some(value),
+            "SpecialBuild" => self.special_build = Some(value),
+            _ => {
+                self.custom.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Representation of the VARFILEINFO block in a versioninfo struct,
+/// listing the language and character set pairs the file supports
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum VarFileInfo {
+    /// one translation per string table, in the same order.
+    /// The block is omitted if there are no string tables
+    #[default]
+    Auto,
+    /// the given translations, independent of the string tables.
+    /// The block is omitted if the list is empty
+    Explicit(Vec<Translation>),
+}
+
+impl VarFileInfo {
+    /// `Auto` if the translations match the string tables, otherwise `Explicit`.
+    /// `None` means the block is missing
+    pub(crate) fn from_translations(translations: Option<Vec<Translation>>, file_info: &[FileInfo]) -> Self {
+        let derived = file_info
+            .iter()
+            .map(|file_info| Translation::new(file_info.lang, file_info.charset));
+        match translations {
+            None if file_info.is_empty() => Self::Auto,
+            None => Self::Explicit(Vec::new()),
+            Some(translations) if translations.iter().copied().eq(derived) => Self::Auto,
+            Some(translations) => Self::Explicit(translations),
+        }
+    }
+}
+
+/// A language and character set pair of the `VarFileInfo` block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Translation {
+    pub lang: Language,
+    pub charset: CharacterSet,
+}
+
+impl Translation {
+    /// the language neutral Unicode translation `0x0000, 1200`
+    pub const NEUTRAL: Self = Self::new(Language::Neutral, CharacterSet::Unicode);
+
+    pub const fn new(lang: Language, charset: CharacterSet) -> Self {
+        Self { lang, charset }
+    }
+
+    /// the translation with the given language id and codepage, if both are known
+    pub fn from_ids(lang: u16, codepage: u16) -> Option<Self> {
+        Some(Self::new(
+            Language::ALL.into_iter().find(|known| known.id() == lang)?,
+            CharacterSet::ALL
+                .into_iter()
+                .find(|known| known.codepage() == codepage)?,
+        ))
+    }
 }
 
 /// The language for the `FileInfo` struct
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
 pub enum Language {
     Arabic,                //0x0401
     Polish,                //0x0415
@@ -318,16 +528,67 @@ pub enum Language {
     BelgianFrench,         //0x080C
     Dutch,                 //0x0413
     CanadianFrench,        //0x0C0C
-    NorwegianBokmal,       //0x041
+    NorwegianBokmal,       //0x0414
     SwissFrench,           //0x100C
     SwissItalian,          //0x0810
     PortuguesePortugal,    //0x0816
     BelgianDutch,          //0x0813
     SerboCyrillicCroatian, //0x081A
     NorwegianNynorsk,      //0x0814
+    Neutral,               //0x0000
 }
 
 impl Language {
+    /// every variant, in declaration order
+    pub const ALL: [Self; 46] = [
+        Self::Arabic,
+        Self::Polish,
+        Self::Bulgarian,
+        Self::PortugueseBrazil,
+        Self::Catalan,
+        Self::RhaetoRomanic,
+        Self::TraditionalChinese,
+        Self::Romanian,
+        Self::Czech,
+        Self::Russian,
+        Self::Danish,
+        Self::CroatoSerbianLatin,
+        Self::German,
+        Self::Slovak,
+        Self::Greek,
+        Self::Albanian,
+        Self::USEnglish,
+        Self::Swedish,
+        Self::CastilianSpanish,
+        Self::Thai,
+        Self::Finnish,
+        Self::Turkish,
+        Self::French,
+        Self::Urdu,
+        Self::Hebrew,
+        Self::Bahasa,
+        Self::Hungarian,
+        Self::SimplifiedChinese,
+        Self::Icelandic,
+        Self::SwissGerman,
+        Self::Italian,
+        Self::UKEnglish,
+        Self::Japanese,
+        Self::SpanishMexico,
+        Self::Korean,
+        Self::BelgianFrench,
+        Self::Dutch,
+        Self::CanadianFrench,
+        Self::NorwegianBokmal,
+        Self::SwissFrench,
+        Self::SwissItalian,
+        Self::PortuguesePortugal,
+        Self::BelgianDutch,
+        Self::SerboCyrillicCroatian,
+        Self::NorwegianNynorsk,
+        Self::Neutral,
+    ];
+
     pub const fn to_hex(&self) -> &str {
         match self {
             Self::Arabic => "0401",
@@ -368,18 +629,115 @@ impl Language {
             Self::BelgianFrench => "080C",
             Self::Dutch => "0413",
             Self::CanadianFrench => "0C0C",
-            Self::NorwegianBokmal => "041",
+            Self::NorwegianBokmal => "0414",
             Self::SwissFrench => "100C",
-            Self::SwissItalian => "810",
+            Self::SwissItalian => "0810",
             Self::PortuguesePortugal => "0816",
-            Self::BelgianDutch => "813",
+            Self::BelgianDutch => "0813",
             Self::SerboCyrillicCroatian => "081A",
             Self::NorwegianNynorsk => "0814",
+            Self::Neutral => "0000",
+        }
+    }
+
+    /// the numeric language id
+    pub fn id(&self) -> u16 {
+        u16::from_str_radix(self.to_hex(), 16).unwrap_or_default()
+    }
+
+    /// the locale tag of the language, e.g. `de-DE` for `German` and `und` for `Neutral`
+    pub const fn locale(&self) -> &str {
+        match self {
+            Self::Arabic => "ar-SA",
+            Self::Polish => "pl-PL",
+            Self::Bulgarian => "bg-BG",
+            Self::PortugueseBrazil => "pt-BR",
+            Self::Catalan => "ca-ES",
+            Self::RhaetoRomanic => "rm-CH",
+            Self::TraditionalChinese => "zh-TW",
+            Self::Romanian => "ro-RO",
+            Self::Czech => "cs-CZ",
+            Self::Russian => "ru-RU",
+            Self::Danish => "da-DK",
+            Self::CroatoSerbianLatin => "hr-HR",
+            Self::German => "de-DE",
+            Self::Slovak => "sk-SK",
+            Self::Greek => "el-GR",
+            Self::Albanian => "sq-AL",
+            Self::USEnglish => "en-US",
+            Self::Swedish => "sv-SE",
+            Self::CastilianSpanish => "es-ES",
+            Self::Thai => "th-TH",
+            Self::Finnish => "fi-FI",
+            Self::Turkish => "tr-TR",
+            Self::French => "fr-FR",
+            Self::Urdu => "ur-PK",
+            Self::Hebrew => "he-IL",
+            Self::Bahasa => "id-ID",
+            Self::Hungarian => "hu-HU",
+            Self::SimplifiedChinese => "zh-CN",
+            Self::Icelandic => "is-IS",
+            Self::SwissGerman => "de-CH",
+            Self::Italian => "it-IT",
+            Self::UKEnglish => "en-GB",
+            Self::Japanese => "ja-JP",
+            Self::SpanishMexico => "es-MX",
+            Self::Korean => "ko-KR",
+            Self::BelgianFrench => "fr-BE",
+            Self::Dutch => "nl-NL",
+            Self::CanadianFrench => "fr-CA",
+            Self::NorwegianBokmal => "nb-NO",
+            Self::SwissFrench => "fr-CH",
+            Self::SwissItalian => "it-CH",
+            Self::PortuguesePortugal => "pt-PT",
+            Self::BelgianDutch => "nl-BE",
+            Self::SerboCyrillicCroatian => "sr-Latn-CS",
+            Self::NorwegianNynorsk => "nn-NO",
+            Self::Neutral => "und",
         }
     }
 }
 
+impl core::str::FromStr for Language {
+    type Err = String;
+
+    /// parses either the variant name (case insensitive), the hexadecimal language id
+    /// or the locale tag (`de-DE` or `de_DE`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = s.trim_start_matches("0x");
+        let locale = s.replace('_', "-");
+        Self::ALL
+            .into_iter()
+            .find(|lang| {
+                format!("{lang:?}").eq_ignore_ascii_case(s)
+                    || lang.to_hex().eq_ignore_ascii_case(id)
+                    || lang.locale().eq_ignore_ascii_case(&locale)
+            })
+            .ok_or_else(|| format!("unknown language `{s}`"))
+    }
+}
+
+impl TryFrom<String> for Language {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Language> for String {
+    fn from(value: Language) -> Self {
+        format!("{value:?}")
+    }
+}
+
 /// The character set for the `FileInfo` struct
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
 pub enum CharacterSet {
     ASCII7bit,             // 0 	0000
     JapanShiftJISX0208,    // 932 	03A4
@@ -396,6 +754,22 @@ pub enum CharacterSet {
 }
 
 impl CharacterSet {
+    /// every variant, in declaration order
+    pub const ALL: [Self; 12] = [
+        Self::ASCII7bit,
+        Self::JapanShiftJISX0208,
+        Self::KoreaShiftKSC5601,
+        Self::TaiwanBig5,
+        Self::Unicode,
+        Self::Latin2EasternEuropean,
+        Self::Cyrillic,
+        Self::Multilingual,
+        Self::Greek,
+        Self::Turkish,
+        Self::Hebrew,
+        Self::Arabic,
+    ];
+
     pub const fn to_hex(&self) -> &str {
         match self {
             Self::ASCII7bit => "0000",
@@ -429,10 +803,46 @@ impl CharacterSet {
             Self::Arabic => "1256",
         }
     }
+
+    /// the numeric codepage
+    pub fn codepage(&self) -> u16 {
+        self.to_decimal().parse().unwrap_or_default()
+    }
+}
+
+impl core::str::FromStr for CharacterSet {
+    type Err = String;
+
+    /// parses either the variant name (case insensitive) or the decimal codepage
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|charset| format!("{charset:?}").eq_ignore_ascii_case(s) || charset.to_decimal() == s)
+            .ok_or_else(|| format!("unknown character set `{s}`"))
+    }
+}
+
+impl TryFrom<String> for CharacterSet {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<CharacterSet> for String {
+    fn from(value: CharacterSet) -> Self {
+        format!("{value:?}")
+    }
 }
 
 /// Wrapper correct string escaping in rc script
-#[derive(Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct RCString(String);
 
 impl From<String> for RCString {
@@ -447,15 +857,48 @@ impl From<&str> for RCString {
     }
 }
 
+impl RCString {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// the string content escaped for use between quotes in a resource script
+    pub(crate) fn escaped(&self) -> String {
+        escape(&self.0)
+    }
+}
+
+/// escapes quotes, backslashes and control characters of a resource script string
+pub(crate) fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str(r#""""#),
+            '\\' => escaped.push_str(r"\\"),
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => escaped.push_str(r"\r"),
+            '\t' => escaped.push_str(r"\t"),
+            '\0' => escaped.push_str(r"\0"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 impl core::fmt::Display for RCString {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, r#""{}\0""#, self.0)
+        write!(f, r#""{}\0""#, self.escaped())
     }
 }
 
 /// wrapper for the actual version, format:
 /// major, minor, patch, build
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
 pub struct Version(pub u16, pub u16, pub u16, pub u16);
 impl core::fmt::Display for Version {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -463,14 +906,55 @@ impl core::fmt::Display for Version {
     }
 }
 
+impl core::str::FromStr for Version {
+    type Err = String;
+
+    /// parses a dotted version like `1.2.3.4`, missing components are set to 0
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = [0; 4];
+        let mut components = s.split('.');
+        for (part, component) in parts.iter_mut().zip(&mut components) {
+            *part = component
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid version `{s}`"))?;
+        }
+        if components.next().is_some() {
+            return Err(format!("version `{s}` has more than four components"));
+        }
+        Ok(Self(parts[0], parts[1], parts[2], parts[3]))
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// formats the version dotted like `1.2.3.4`
+impl From<Version> for String {
+    fn from(value: Version) -> Self {
+        format!("{}.{}.{}.{}", value.0, value.1, value.2, value.3)
+    }
+}
+
 /// is always 0x3f
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileFlagMask {
     Win16, // = 0x3f, // THERE IS ONLY ONE OPTION
     Custom(u32),
 }
 
+/// `{:#}` writes the numeric value instead of the `winver.h` macro
 impl core::fmt::Display for FileFlagMask {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{:#X}", self.value());
+        }
         match self {
             Self::Win16 => write!(f, "VS_FFI_FILEFLAGSMASK"),
             Self::Custom(i) => write!(f, "{i}"),
@@ -478,7 +962,41 @@ impl core::fmt::Display for FileFlagMask {
     }
 }
 
+impl FileFlagMask {
+    pub(crate) const fn value(&self) -> u32 {
+        match self {
+            Self::Win16 => 0x3F,
+            Self::Custom(i) => *i,
+        }
+    }
+
+    pub(crate) const fn from_value(value: u32) -> Self {
+        match value {
+            0x3F => Self::Win16,
+            value => Self::Custom(value),
+        }
+    }
+}
+
+impl From<FileFlagMask> for u32 {
+    fn from(value: FileFlagMask) -> Self {
+        value.value()
+    }
+}
+
+impl From<u32> for FileFlagMask {
+    fn from(value: u32) -> Self {
+        Self::from_value(value)
+    }
+}
+
 /// special flags descirbing certain attributes (look at flag descriptions)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct FileFlags {
     /// File contains debugging information or is compiled with debugging features enabled.
     pub debug: bool,
@@ -494,16 +1012,126 @@ pub struct FileFlags {
     pub specialbuild: bool,
 }
 
+impl FileFlags {
+    /// `VS_FF_DEBUG`
+    pub const DEBUG: Self = Self::from_value(0x01);
+    /// `VS_FF_PRERELEASE`
+    pub const PRERELEASE: Self = Self::from_value(0x02);
+    /// `VS_FF_PATCHED`
+    pub const PATCHED: Self = Self::from_value(0x04);
+    /// `VS_FF_PRIVATEBUILD`
+    pub const PRIVATEBUILD: Self = Self::from_value(0x08);
+    /// `VS_FF_INFOINFERRED`
+    pub const INFOINFERRED: Self = Self::from_value(0x10);
+    /// `VS_FF_SPECIALBUILD`
+    pub const SPECIALBUILD: Self = Self::from_value(0x20);
+    /// no flags set
+    pub const NONE: Self = Self::from_value(0);
+    /// all flags set
+    pub const ALL: Self = Self::from_value(0x3F);
+
+    /// whether all flags set in `other` are set in `self` as well
+    pub const fn contains(&self, other: Self) -> bool {
+        self.value() & other.value() == other.value()
+    }
+
+    /// whether no flag is set
+    pub const fn is_empty(&self) -> bool {
+        self.value() == 0
+    }
+
+    pub(crate) const fn value(&self) -> u32 {
+        (self.debug as u32)
+            | (self.prerelease as u32) << 1
+            | (self.patched as u32) << 2
+            | (self.privatebuild as u32) << 3
+            | (self.infoinferred as u32) << 4
+            | (self.specialbuild as u32) << 5
+    }
+
+    pub(crate) const fn from_value(value: u32) -> Self {
+        Self {
+            debug: value & 0x01 != 0,
+            prerelease: value & 0x02 != 0,
+            patched: value & 0x04 != 0,
+            privatebuild: value & 0x08 != 0,
+            infoinferred: value & 0x10 != 0,
+            specialbuild: value & 0x20 != 0,
+        }
+    }
+}
+
+impl From<FileFlags> for u32 {
+    fn from(value: FileFlags) -> Self {
+        value.value()
+    }
+}
+
+/// bits not representable by `FileFlags` are dropped
+impl From<u32> for FileFlags {
+    fn from(value: u32) -> Self {
+        Self::from_value(value)
+    }
+}
+
+impl core::ops::BitOr for FileFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self::from_value(self.value() | rhs.value())
+    }
+}
+
+impl core::ops::BitAnd for FileFlags {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self::from_value(self.value() & rhs.value())
+    }
+}
+
+impl core::ops::BitXor for FileFlags {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        Self::from_value(self.value() ^ rhs.value())
+    }
+}
+
+impl core::ops::Not for FileFlags {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::from_value(!self.value())
+    }
+}
+
+impl core::ops::BitOrAssign for FileFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = *self | rhs;
+    }
+}
+
+impl core::ops::BitAndAssign for FileFlags {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = *self & rhs;
+    }
+}
+
+/// `{:#}` writes the numeric value instead of the `winver.h` macros
 impl core::fmt::Display for FileFlags {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{:#X}", self.value());
+        }
         let mut flags = Vec::new();
         if self.debug {
             flags.push("VS_FF_DEBUG");
         }
-        if self.patched {
+        if self.prerelease {
             flags.push("VS_FF_PRERELEASE");
         }
-        if self.prerelease {
+        if self.patched {
             flags.push("VS_FF_PATCHED");
         }
         if self.privatebuild {
@@ -525,6 +1153,8 @@ impl core::fmt::Display for FileFlags {
 
 /// the operating system the application is designed for.
 /// the default in the microsoft documentation is Windows32
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileOS {
     Unknown,      // = 0x00000000,
     Dos,          // = 0x00010000,
@@ -568,15 +1198,97 @@ impl FileOS {
             Self::Custom(i) => i.to_string(),
         }
     }
+
+    pub(crate) const fn value(&self) -> u32 {
+        match self {
+            Self::Unknown | Self::Base => 0x0000_0000,
+            Self::Dos => 0x0001_0000,
+            Self::OS216Bit => 0x0002_0000,
+            Self::OS232Bit => 0x0003_0000,
+            Self::NT => 0x0004_0000,
+            Self::WinCE => 0x0005_0000,
+            Self::Windows16 => 0x0000_0001,
+            Self::PM16 => 0x0000_0002,
+            Self::PM32 => 0x0000_0003,
+            Self::Windows32 => 0x0000_0004,
+            Self::DosWindows16 => 0x0001_0001,
+            Self::DosWindows32 => 0x0001_0004,
+            Self::OS216BitPM16 => 0x0002_0002,
+            Self::OS232BitPM32 => 0x0003_0003,
+            Self::NTWindows32 => 0x0004_0004,
+            Self::Custom(i) => *i,
+        }
+    }
+
+    pub(crate) const fn from_value(value: u32) -> Self {
+        match value {
+            0x0000_0000 => Self::Unknown,
+            0x0001_0000 => Self::Dos,
+            0x0002_0000 => Self::OS216Bit,
+            0x0003_0000 => Self::OS232Bit,
+            0x0004_0000 => Self::NT,
+            0x0005_0000 => Self::WinCE,
+            0x0000_0001 => Self::Windows16,
+            0x0000_0002 => Self::PM16,
+            0x0000_0003 => Self::PM32,
+            0x0000_0004 => Self::Windows32,
+            0x0001_0001 => Self::DosWindows16,
+            0x0001_0004 => Self::DosWindows32,
+            0x0002_0002 => Self::OS216BitPM16,
+            0x0003_0003 => Self::OS232BitPM32,
+            0x0004_0004 => Self::NTWindows32,
+            value => Self::Custom(value),
+        }
+    }
+
+    /// the operating system part, e.g. `NT` for `NTWindows32`
+    pub const fn os(&self) -> Self {
+        Self::from_value(self.value() & 0xFFFF_0000)
+    }
+
+    /// the window system part, e.g. `Windows32` for `NTWindows32`
+    pub const fn window_system(&self) -> Self {
+        match self.value() & 0x0000_FFFF {
+            0 => Self::Base,
+            value => Self::from_value(value),
+        }
+    }
+}
+
+impl From<FileOS> for u32 {
+    fn from(value: FileOS) -> Self {
+        value.value()
+    }
+}
+
+impl From<u32> for FileOS {
+    fn from(value: u32) -> Self {
+        Self::from_value(value)
+    }
+}
+
+/// combines an operating system with a window system, e.g. `FileOS::NT | FileOS::Windows32`
+impl core::ops::BitOr for FileOS {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self::from_value(self.value() | rhs.value())
+    }
 }
 
+/// `{:#}` writes the numeric value instead of the `winver.h` macro
 impl core::fmt::Display for FileOS {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{:#X}", self.value());
+        }
         write!(f, "{}", self.get_value())
     }
 }
 
 /// The file type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileType {
     Unknown,               // = 0x00000000,
     App,                   // = 0x00000001,
@@ -610,15 +1322,72 @@ impl FileType {
             _ => "0".to_string(),
         }
     }
+
+    pub(crate) const fn value(&self) -> u32 {
+        match self {
+            Self::Unknown => 0,
+            Self::App => 1,
+            Self::Dll => 2,
+            Self::Driver(_) => 3,
+            Self::Font(_) => 4,
+            Self::VXD => 5,
+            Self::StaticLibrary => 7,
+            Self::Custom(i, _) => *i,
+        }
+    }
+
+    /// the numeric value of `FILESUBTYPE`
+    pub const fn subtype_value(&self) -> u32 {
+        match self {
+            Self::Driver(t) => t.value(),
+            Self::Font(t) => t.value(),
+            Self::Custom(_, i) => *i,
+            _ => 0,
+        }
+    }
+
+    /// creates the file type from the numeric values of `FILETYPE` and `FILESUBTYPE`
+    pub const fn from_values(value: u32, subtype: u32) -> Self {
+        match (value, subtype) {
+            (0, 0) => Self::Unknown,
+            (1, 0) => Self::App,
+            (2, 0) => Self::Dll,
+            (3, subtype) => Self::Driver(SubTypeDriver::from_value(subtype)),
+            (4, subtype) => Self::Font(SubTypeFont::from_value(subtype)),
+            (5, 0) => Self::VXD,
+            (7, 0) => Self::StaticLibrary,
+            (value, subtype) => Self::Custom(value, subtype),
+        }
+    }
 }
 
+/// the numeric value of `FILETYPE`, use `subtype_value` for the subtype
+impl From<FileType> for u32 {
+    fn from(value: FileType) -> Self {
+        value.value()
+    }
+}
+
+/// creates the file type without a subtype, use `from_values` to include it
+impl From<u32> for FileType {
+    fn from(value: u32) -> Self {
+        Self::from_values(value, 0)
+    }
+}
+
+/// `{:#}` writes the numeric value instead of the `winver.h` macro
 impl core::fmt::Display for FileType {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{:#X}", self.value());
+        }
         write!(f, "{}", self.get_value())
     }
 }
 
 /// file subtype for driver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SubTypeDriver {
     Unknown,          // = 0x00000000,
     Printer,          // = 0x00000001,
@@ -655,15 +1424,71 @@ impl SubTypeDriver {
             Self::Custom(i) => i.to_string(),
         }
     }
+
+    pub(crate) const fn value(&self) -> u32 {
+        match self {
+            Self::Unknown => 0x0,
+            Self::Printer => 0x1,
+            Self::Keyboard => 0x2,
+            Self::Language => 0x3,
+            Self::Display => 0x4,
+            Self::Mouse => 0x5,
+            Self::Network => 0x6,
+            Self::System => 0x7,
+            Self::Installable => 0x8,
+            Self::Sound => 0x9,
+            Self::Comm => 0xA,
+            Self::InputMethod => 0xB,
+            Self::VersionedPrinter => 0xC,
+            Self::Custom(i) => *i,
+        }
+    }
+
+    pub(crate) const fn from_value(value: u32) -> Self {
+        match value {
+            0x0 => Self::Unknown,
+            0x1 => Self::Printer,
+            0x2 => Self::Keyboard,
+            0x3 => Self::Language,
+            0x4 => Self::Display,
+            0x5 => Self::Mouse,
+            0x6 => Self::Network,
+            0x7 => Self::System,
+            0x8 => Self::Installable,
+            0x9 => Self::Sound,
+            0xA => Self::Comm,
+            0xB => Self::InputMethod,
+            0xC => Self::VersionedPrinter,
+            value => Self::Custom(value),
+        }
+    }
+}
+
+impl From<SubTypeDriver> for u32 {
+    fn from(value: SubTypeDriver) -> Self {
+        value.value()
+    }
 }
 
+impl From<u32> for SubTypeDriver {
+    fn from(value: u32) -> Self {
+        Self::from_value(value)
+    }
+}
+
+/// `{:#}` writes the numeric value instead of the `winver.h` macro
 impl core::fmt::Display for SubTypeDriver {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{:#X}", self.value());
+        }
         write!(f, "{}", self.get_value())
     }
 }
 
 /// file subtype for fonts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SubTypeFont {
     RasterFont,   // = 0x00000001,
     VectorFont,   // = 0x00000002,
@@ -680,10 +1505,44 @@ impl SubTypeFont {
             Self::Custom(i) => i.to_string(),
         }
     }
+
+    pub(crate) const fn value(&self) -> u32 {
+        match self {
+            Self::RasterFont => 1,
+            Self::VectorFont => 2,
+            Self::TrueTypeFont => 3,
+            Self::Custom(i) => *i,
+        }
+    }
+
+    pub(crate) const fn from_value(value: u32) -> Self {
+        match value {
+            1 => Self::RasterFont,
+            2 => Self::VectorFont,
+            3 => Self::TrueTypeFont,
+            value => Self::Custom(value),
+        }
+    }
+}
+
+impl From<SubTypeFont> for u32 {
+    fn from(value: SubTypeFont) -> Self {
+        value.value()
+    }
 }
 
+impl From<u32> for SubTypeFont {
+    fn from(value: u32) -> Self {
+        Self::from_value(value)
+    }
+}
+
+/// `{:#}` writes the numeric value instead of the `winver.h` macro
 impl core::fmt::Display for SubTypeFont {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{:#X}", self.value());
+        }
         write!(f, "{}", self.get_value())
     }
 }
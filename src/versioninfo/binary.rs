@@ -0,0 +1,361 @@
+//! Serialization into the binary `VS_VERSIONINFO` layout stored in executables
+use super::*;
+
+const FIXED_FILE_INFO_SIGNATURE: u32 = 0xFEEF04BD;
+const FIXED_FILE_INFO_STRUCT_VERSION: u32 = 0x0001_0000;
+const FIXED_FILE_INFO_LENGTH: u16 = 52;
+/// `wType` of nodes holding binary data
+const TYPE_BINARY: u16 = 0;
+/// `wType` of nodes holding text
+const TYPE_TEXT: u16 = 1;
+/// resource type id of `RT_VERSION`
+const RT_VERSION: u16 = 16;
+
+/// Error returned when binary version information can not be decoded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    /// byte offset the error occurred at
+    pub offset: usize,
+    pub message: String,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "offset {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Error returned when version information does not fit into the binary layout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodeError {
+    /// the key of the node that is too long
+    pub key: String,
+    /// the length the node would have, in bytes for the node and in the unit of the value otherwise
+    pub length: usize,
+}
+
+impl core::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the node {} is too long ({}), at most 65535 is possible", self.key, self.length)
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// the length as the 16 bit value stored in a node header
+fn checked_length(key: &str, length: usize) -> Result<u16, EncodeError> {
+    u16::try_from(length).map_err(|_| EncodeError {
+        key: key.to_owned(),
+        length,
+    })
+}
+
+fn pad(buffer: &mut Vec<u8>) {
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+}
+
+fn push_utf16(buffer: &mut Vec<u8>, text: &str) {
+    for unit in text.encode_utf16().chain([0]) {
+        buffer.extend_from_slice(&unit.to_le_bytes());
+    }
+}
+
+/// writes one node: the header, key and value followed by the 32 bit aligned children.
+/// `value_length` is counted in bytes for binary and in characters for text nodes
+fn node(
+    key: &str,
+    value_type: u16,
+    value_length: usize,
+    value: &[u8],
+    children: &[Vec<u8>],
+) -> Result<Vec<u8>, EncodeError> {
+    let mut buffer = vec![0; 2];
+    buffer.extend_from_slice(&checked_length(key, value_length)?.to_le_bytes());
+    buffer.extend_from_slice(&value_type.to_le_bytes());
+    push_utf16(&mut buffer, key);
+    pad(&mut buffer);
+    buffer.extend_from_slice(value);
+    for child in children {
+        pad(&mut buffer);
+        buffer.extend_from_slice(child);
+    }
+    let length = checked_length(key, buffer.len())?;
+    buffer[..2].copy_from_slice(&length.to_le_bytes());
+    Ok(buffer)
+}
+
+fn string_node(key: &str, value: &RCString) -> Result<Vec<u8>, EncodeError> {
+    let mut text = Vec::new();
+    push_utf16(&mut text, value.as_str());
+    node(key, TYPE_TEXT, text.len() / 2, &text, &[])
+}
+
+impl VersionInfo {
+    /// Serializes the version information into the binary `VS_VERSIONINFO` layout,
+    /// as it is stored in the `RT_VERSION` resource of an executable.
+    /// Fails if a string or the whole structure is longer than the 16 bit lengths of the layout allow
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut fixed = Vec::with_capacity(FIXED_FILE_INFO_LENGTH as usize);
+        for value in [
+            FIXED_FILE_INFO_SIGNATURE,
+            FIXED_FILE_INFO_STRUCT_VERSION,
+            (self.file_version.0 as u32) << 16 | self.file_version.1 as u32,
+            (self.file_version.2 as u32) << 16 | self.file_version.3 as u32,
+            (self.product_version.0 as u32) << 16 | self.product_version.1 as u32,
+            (self.product_version.2 as u32) << 16 | self.product_version.3 as u32,
+            self.file_flag_mask.value(),
+            self.file_flags.value(),
+            self.file_os.value(),
+            self.file_type.value(),
+            self.file_type.subtype_value(),
+            // the file date is never set, keeping the output reproducible
+            0,
+            0,
+        ] {
+            fixed.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let mut children = Vec::new();
+        if !self.file_info.is_empty() {
+            let tables: Vec<_> = self
+                .file_info
+                .iter()
+                .map(|file_info| {
+                    let strings = file_info
+                        .values()
+                        .into_iter()
+                        .map(|(key, value)| string_node(key, value))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let key = format!("{}{}", file_info.lang.to_hex(), file_info.charset.to_hex());
+                    node(&key, TYPE_TEXT, 0, &[], &strings)
+                })
+                .collect::<Result<_, _>>()?;
+            children.push(node("StringFileInfo", TYPE_TEXT, 0, &[], &tables)?);
+        }
+
+        let translations = self.translations();
+        if !translations.is_empty() {
+            let mut value = Vec::new();
+            for translation in &translations {
+                value.extend_from_slice(&translation.lang.id().to_le_bytes());
+                value.extend_from_slice(&translation.charset.codepage().to_le_bytes());
+            }
+            let translation = node("Translation", TYPE_BINARY, value.len(), &value, &[])?;
+            children.push(node("VarFileInfo", TYPE_TEXT, 0, &[], &[translation])?);
+        }
+
+        node(
+            "VS_VERSION_INFO",
+            TYPE_BINARY,
+            FIXED_FILE_INFO_LENGTH as usize,
+            &fixed,
+            &children,
+        )
+    }
+
+    /// Wraps [`Self::to_bytes`] into a `.res` file as produced by resource compilers
+    pub fn to_res(&self) -> Result<Vec<u8>, EncodeError> {
+        let data = self.to_bytes()?;
+        let language = self
+            .translations()
+            .first()
+            .map_or(0, |translation| translation.lang.id());
+
+        // a .res file starts with an empty resource entry
+        let mut res = vec![0, 0, 0, 0, 0x20, 0, 0, 0, 0xFF, 0xFF, 0, 0, 0xFF, 0xFF, 0, 0];
+        res.resize(32, 0);
+
+        res.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        res.extend_from_slice(&32u32.to_le_bytes()); // header size
+        res.extend_from_slice(&[0xFF, 0xFF]);
+        res.extend_from_slice(&RT_VERSION.to_le_bytes());
+        res.extend_from_slice(&[0xFF, 0xFF]);
+        res.extend_from_slice(&1u16.to_le_bytes()); // VS_VERSION_INFO
+        res.extend_from_slice(&0u32.to_le_bytes()); // data version
+        res.extend_from_slice(&0x0030u16.to_le_bytes()); // MOVEABLE | PURE
+        res.extend_from_slice(&language.to_le_bytes());
+        res.extend_from_slice(&0u32.to_le_bytes()); // version
+        res.extend_from_slice(&0u32.to_le_bytes()); // characteristics
+        res.extend_from_slice(&data);
+        pad(&mut res);
+        Ok(res)
+    }
+
+    /// Decodes the binary `VS_VERSIONINFO` layout produced by [`Self::to_bytes`]
+    /// or read from the resources of an executable.
+    /// `var_file_info` is `Auto` if the translations match the string tables.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let root = Node::read(bytes, 0)?;
+        if root.key != "VS_VERSION_INFO" {
+            return root.error(format!("expected VS_VERSION_INFO, found {}", root.key));
+        }
+        if root.value.len() < FIXED_FILE_INFO_LENGTH as usize {
+            return root.error("the fixed file info is missing");
+        }
+        let fixed: Vec<u32> = root
+            .value
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        if fixed[0] != FIXED_FILE_INFO_SIGNATURE {
+            return root.error(format!("invalid fixed file info signature {:#X}", fixed[0]));
+        }
+
+        let version = |ms: u32, ls: u32| Version((ms >> 16) as u16, ms as u16, (ls >> 16) as u16, ls as u16);
+        let mut version_info = VersionInfo {
+            file_version: version(fixed[2], fixed[3]),
+            product_version: version(fixed[4], fixed[5]),
+            file_flag_mask: FileFlagMask::from_value(fixed[6]),
+            file_flags: FileFlags::from_value(fixed[7]),
+            file_os: FileOS::from_value(fixed[8]),
+            file_type: FileType::from_values(fixed[9], fixed[10]),
+            file_info: Vec::new(),
+            var_file_info: VarFileInfo::Auto,
+        };
+
+        let mut translations = None;
+        for child in root.children()? {
+            if child.key == "VarFileInfo" {
+                for var in child.children()? {
+                    if var.key != "Translation" {
+                        continue;
+                    }
+                    let list = translations.get_or_insert_with(Vec::new);
+                    for pair in var.value.chunks_exact(4) {
+                        let lang = u16::from_le_bytes([pair[0], pair[1]]);
+                        let codepage = u16::from_le_bytes([pair[2], pair[3]]);
+                        let Some(translation) = Translation::from_ids(lang, codepage) else {
+                            return var.error(format!("unknown translation {lang:#06X}, {codepage}"));
+                        };
+                        list.push(translation);
+                    }
+                }
+                continue;
+            }
+            if child.key != "StringFileInfo" {
+                continue;
+            }
+            for table in child.children()? {
+                let (Some(lang), Some(charset)) = (table.key.get(..4), table.key.get(4..)) else {
+                    return table.error(format!("invalid string table key {}", table.key));
+                };
+                let Some(lang) = Language::ALL
+                    .into_iter()
+                    .find(|known| known.to_hex().eq_ignore_ascii_case(lang))
+                else {
+                    return table.error(format!("unknown language id {lang}"));
+                };
+                let Some(charset) = CharacterSet::ALL
+                    .into_iter()
+                    .find(|known| known.to_hex().eq_ignore_ascii_case(charset))
+                else {
+                    return table.error(format!("unknown character set id {charset}"));
+                };
+
+                let mut file_info = FileInfo::empty(lang, charset);
+                for string in table.children()? {
+                    let value = string.text();
+                    file_info.insert(string.key.clone(), value.trim_end_matches('\0').into());
+                }
+                version_info.file_info.push(file_info);
+            }
+        }
+        version_info.var_file_info = VarFileInfo::from_translations(translations, &version_info.file_info);
+        Ok(version_info)
+    }
+}
+
+/// a decoded node of the version information tree
+struct Node<'a> {
+    offset: usize,
+    key: String,
+    value: &'a [u8],
+    /// the bytes of all children including their alignment
+    children: &'a [u8],
+    /// offset of `children` in the whole buffer
+    children_offset: usize,
+}
+
+impl<'a> Node<'a> {
+    /// reads the node at the start of `bytes`, which is located at `offset` of the whole buffer
+    fn read(bytes: &'a [u8], offset: usize) -> Result<Self, DecodeError> {
+        let error = |message: &str| DecodeError {
+            offset,
+            message: message.into(),
+        };
+        let word = |at: usize| {
+            bytes
+                .get(at..at + 2)
+                .map(|word| u16::from_le_bytes([word[0], word[1]]))
+                .ok_or_else(|| error("unexpected end of data"))
+        };
+        let length = word(0)? as usize;
+        let value_length = word(2)? as usize;
+        let value_type = word(4)?;
+        if length > bytes.len() || length < 6 {
+            return Err(error("invalid node length"));
+        }
+        let bytes = &bytes[..length];
+
+        let mut key = Vec::new();
+        let mut position = 6;
+        loop {
+            let unit = word(position)?;
+            position += 2;
+            if unit == 0 {
+                break;
+            }
+            key.push(unit);
+        }
+        let key = String::from_utf16(&key).map_err(|_| error("invalid key"))?;
+        position = position.next_multiple_of(4);
+
+        let value_size = if value_type == TYPE_TEXT {
+            value_length * 2
+        } else {
+            value_length
+        };
+        let value = bytes
+            .get(position.min(length)..(position + value_size).min(length))
+            .unwrap_or_default();
+        let children_offset = (position + value_size).next_multiple_of(4).min(length);
+
+        Ok(Self {
+            offset,
+            key,
+            value,
+            children: &bytes[children_offset..],
+            children_offset: offset + children_offset,
+        })
+    }
+
+    fn children(&self) -> Result<Vec<Node<'a>>, DecodeError> {
+        let mut children = Vec::new();
+        let mut position = 0;
+        while position < self.children.len() {
+            let child = Node::read(&self.children[position..], self.children_offset + position)?;
+            let length = u16::from_le_bytes([self.children[position], self.children[position + 1]]);
+            position = (position + length as usize).next_multiple_of(4);
+            children.push(child);
+        }
+        Ok(children)
+    }
+
+    fn text(&self) -> String {
+        let units: Vec<u16> = self
+            .value
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, DecodeError> {
+        Err(DecodeError {
+            offset: self.offset,
+            message: message.into(),
+        })
+    }
+}
@@ -0,0 +1,116 @@
+//! Encoding of the string tables in their declared character set,
+//! used when the resource compiler reads the script as bytes
+use super::*;
+use encoding_rs::Encoding;
+
+impl CharacterSet {
+    /// the codepage the resource compiler is switched to for string tables of this character set
+    pub(crate) const fn script_codepage(&self) -> u16 {
+        match self {
+            // plain ASCII is valid in every ANSI codepage
+            Self::ASCII7bit => 1252,
+            Self::Unicode => 65001,
+            Self::JapanShiftJISX0208 => 932,
+            Self::KoreaShiftKSC5601 => 949,
+            Self::TaiwanBig5 => 950,
+            Self::Latin2EasternEuropean => 1250,
+            Self::Cyrillic => 1251,
+            Self::Multilingual => 1252,
+            Self::Greek => 1253,
+            Self::Turkish => 1254,
+            Self::Hebrew => 1255,
+            Self::Arabic => 1256,
+        }
+    }
+
+    fn encoding(&self) -> &'static Encoding {
+        match self {
+            Self::ASCII7bit | Self::Multilingual => encoding_rs::WINDOWS_1252,
+            Self::Unicode => encoding_rs::UTF_8,
+            Self::JapanShiftJISX0208 => encoding_rs::SHIFT_JIS,
+            Self::KoreaShiftKSC5601 => encoding_rs::EUC_KR,
+            Self::TaiwanBig5 => encoding_rs::BIG5,
+            Self::Latin2EasternEuropean => encoding_rs::WINDOWS_1250,
+            Self::Cyrillic => encoding_rs::WINDOWS_1251,
+            Self::Greek => encoding_rs::WINDOWS_1253,
+            Self::Turkish => encoding_rs::WINDOWS_1254,
+            Self::Hebrew => encoding_rs::WINDOWS_1255,
+            Self::Arabic => encoding_rs::WINDOWS_1256,
+        }
+    }
+
+    /// encodes `text` in the codepage of the character set.
+    /// Returns the first character that can not be encoded as error
+    pub(crate) fn encode(&self, text: &str) -> Result<Vec<u8>, char> {
+        if *self == Self::ASCII7bit {
+            if let Some(character) = text.chars().find(|character| !character.is_ascii()) {
+                return Err(character);
+            }
+        }
+        let (bytes, _, unmappable) = self.encoding().encode(text);
+        if !unmappable {
+            return Ok(bytes.into_owned());
+        }
+        let mut buffer = [0; 4];
+        Err(text
+            .chars()
+            .find(|character| self.encoding().encode(character.encode_utf8(&mut buffer)).2)
+            .unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+}
+
+impl FileInfo {
+    /// the first string that can not be encoded in the character set of the string table
+    pub(crate) fn unencodable_string(&self) -> Option<Diagnostic> {
+        self.values().into_iter().find_map(|(key, value)| {
+            let character = self
+                .charset
+                .encode(key)
+                .and_then(|_| self.charset.encode(value.as_str()))
+                .err()?;
+            Some(Diagnostic::UnencodableString {
+                lang: self.lang,
+                charset: self.charset,
+                key: key.to_owned(),
+                character,
+            })
+        })
+    }
+}
+
+impl VersionInfo {
+    /// Encodes the `{:#}` resource script as bytes.
+    /// Every string table is preceded by a `#pragma code_page` and encoded in the codepage
+    /// of its character set, everything else is ASCII.
+    pub(crate) fn encoded_script(&self) -> Result<Vec<u8>, Diagnostic> {
+        if let Some(diagnostic) = self.file_info.iter().find_map(FileInfo::unencodable_string) {
+            return Err(diagnostic);
+        }
+        let script = format!("{self:#}");
+        let mut tables = self.file_info.iter();
+        let mut current: Option<&FileInfo> = None;
+        let mut encoded = Vec::with_capacity(script.len());
+        for line in script.split_inclusive('\n') {
+            // string tables are the only blocks indented by two spaces
+            if current.is_none() && line.starts_with("  BLOCK \"") {
+                current = tables.next();
+                if let Some(file_info) = current {
+                    let pragma = format!("#pragma code_page({})\n", file_info.charset.script_codepage());
+                    encoded.extend_from_slice(pragma.as_bytes());
+                }
+            } else if current.is_some() && line == "  END\n" {
+                current = None;
+            }
+            match current {
+                Some(file_info) => encoded.extend(
+                    file_info
+                        .charset
+                        .encode(line)
+                        .expect("the strings were checked before"),
+                ),
+                None => encoded.extend_from_slice(line.as_bytes()),
+            }
+        }
+        Ok(encoded)
+    }
+}
@@ -0,0 +1,135 @@
+//! Version metadata read from the git repository of the crate using the `git` command
+use super::*;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::process::Command;
+
+/// runs `git` in `dir`, returning the trimmed output if it succeeds
+fn git(dir: &Utf8Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// State of the git repository the crate is built from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitInfo {
+    /// hash of the checked out commit
+    pub commit: String,
+    /// abbreviated hash of the checked out commit
+    pub short_commit: String,
+    /// whether the work tree has uncommitted changes
+    pub dirty: bool,
+    /// the most recent tag reachable from the commit
+    pub tag: Option<String>,
+    /// number of commits since `tag` or since the first commit if there is no tag
+    pub commits_since_tag: u32,
+}
+
+impl GitInfo {
+    /// Reads the repository containing `CARGO_MANIFEST_DIR`.
+    /// Returns `None` if the crate is not in a git repository or `git` is not installed.
+    /// No `cargo:rerun-if-changed` directives are printed, see `rerun_if_changed`
+    pub fn read() -> Option<Self> {
+        Self::read_from(var("CARGO_MANIFEST_DIR").ok()?)
+    }
+
+    /// like `read`, but for the repository containing `dir`
+    pub fn read_from<P: AsRef<Utf8Path>>(dir: P) -> Option<Self> {
+        let dir = dir.as_ref();
+        let commit = git(dir, &["rev-parse", "HEAD"])?;
+        let describe = git(dir, &["describe", "--tags", "--long", "--dirty", "--always"])?;
+        let mut info = Self::from_describe(&describe, commit);
+        if info.tag.is_none() {
+            info.commits_since_tag = git(dir, &["rev-list", "--count", "HEAD"])?.parse().ok()?;
+        }
+        Some(info)
+    }
+
+    /// Tells cargo to rerun the build script when the checked out commit of the repository
+    /// containing `CARGO_MANIFEST_DIR` changes.
+    /// The first `rerun-if` directive stops cargo from rerunning the build script on every change of the package,
+    /// so the other inputs of the build script have to be declared as well
+    pub fn rerun_if_changed() {
+        if let Ok(dir) = var("CARGO_MANIFEST_DIR") {
+            Self::rerun_if_changed_in(dir);
+        }
+    }
+
+    /// like `rerun_if_changed`, but for the repository containing `dir`
+    pub fn rerun_if_changed_in<P: AsRef<Utf8Path>>(dir: P) {
+        for file in Self::watched_files(dir.as_ref()).unwrap_or_default() {
+            println!("cargo:rerun-if-changed={file}");
+        }
+    }
+
+    /// the files of the repository changing with the checked out commit.
+    /// `HEAD` and `index` belong to the work tree, the refs are shared by all work trees of the repository
+    pub(crate) fn watched_files(dir: &Utf8Path) -> Option<Vec<Utf8PathBuf>> {
+        let git_dir = Utf8PathBuf::from(git(dir, &["rev-parse", "--absolute-git-dir"])?);
+        // relative to `dir` unless it is outside of the repository
+        let common_dir = dir.join(git(dir, &["rev-parse", "--git-common-dir"])?);
+        let mut files = vec![git_dir.join("HEAD"), git_dir.join("index")];
+        if let Some(head) = git(dir, &["symbolic-ref", "-q", "HEAD"]) {
+            files.push(common_dir.join(head));
+        }
+        files.push(common_dir.join("packed-refs"));
+        Some(files)
+    }
+
+    /// parses the output of `git describe --tags --long --dirty --always`
+    pub(crate) fn from_describe(describe: &str, commit: String) -> Self {
+        let (describe, dirty) = match describe.strip_suffix("-dirty") {
+            Some(describe) => (describe, true),
+            None => (describe, false),
+        };
+        let mut parts = describe.rsplitn(3, '-');
+        let (short_commit, count, tag) = (parts.next(), parts.next(), parts.next());
+        match (short_commit.and_then(|hash| hash.strip_prefix('g')), count.map(str::parse), tag) {
+            (Some(short_commit), Some(Ok(commits_since_tag)), Some(tag)) => Self {
+                commit,
+                short_commit: short_commit.to_owned(),
+                dirty,
+                tag: Some(tag.to_owned()),
+                commits_since_tag,
+            },
+            // without a tag only the abbreviated hash is printed
+            _ => Self {
+                commit,
+                short_commit: describe.to_owned(),
+                dirty,
+                tag: None,
+                commits_since_tag: 0,
+            },
+        }
+    }
+
+    /// the text used for the `PrivateBuild` string, e.g. `commit 1a2b3c4 (dirty)`
+    pub fn description(&self) -> String {
+        if self.dirty {
+            format!("commit {} (dirty)", self.short_commit)
+        } else {
+            format!("commit {}", self.short_commit)
+        }
+    }
+}
+
+impl VersionInfo {
+    /// Adds the repository state to the version information:
+    /// the string tables without a `PrivateBuild` string get `GitInfo::description` and the `privatebuild` flag is set for them,
+    /// existing `PrivateBuild` strings are kept.
+    /// The `patched` flag is set for uncommitted changes, but never cleared,
+    /// and the last component of the file version is the number of commits since the last tag
+    pub fn with_git(mut self, git: &GitInfo) -> Self {
+        self.file_flags.patched |= git.dirty;
+        self.file_version.3 = u16::try_from(git.commits_since_tag).unwrap_or(u16::MAX);
+        for file_info in &mut self.file_info {
+            if file_info.private_build.is_none() {
+                file_info.private_build = Some(git.description().into());
+                self.file_flags.privatebuild = true;
+            }
+        }
+        self
+    }
+}
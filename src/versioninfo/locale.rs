@@ -0,0 +1,194 @@
+//! Localized string tables loaded from a directory of translation files.
+//!
+//! Every `LOCALE.toml` or `LOCALE.ftl` file, e.g. `de-DE.toml`, results in one [`FileInfo`].
+//! The keys are the names of the version strings, unknown keys become custom strings.
+//! ```toml
+//! FileDescription = "Beispielprogramm"
+//! LegalCopyright = "Copyright (c) Beispiel GmbH"
+//! Homepage = "https://example.com/de"
+//! # optional, defaults to Unicode
+//! -charset = "Unicode"
+//! ```
+//! The character set is read from the `-charset` key in both formats, as no version string name starts with `-`.
+//! Fluent files support simple messages, the character set is set with the `-charset` term.
+//! ```ftl
+//! FileDescription = Programme d'exemple
+//! -charset = 1252
+//! ```
+use super::*;
+use camino::{Utf8Path, Utf8PathBuf};
+
+/// Error returned when a translation file can not be loaded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocaleError {
+    pub path: Utf8PathBuf,
+    /// line of the file the error occurred in, starting at 1. 0 if the error is not related to a line
+    pub line: usize,
+    pub message: String,
+}
+
+impl core::fmt::Display for LocaleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}: {}", self.path, self.message)
+        } else {
+            write!(f, "{}:{}: {}", self.path, self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for LocaleError {}
+
+/// the content of a translation file
+struct LocaleFile {
+    charset: Option<String>,
+    strings: Vec<(String, String)>,
+}
+
+impl FileInfo {
+    /// Loads one `FileInfo` per translation file in `dir`, ordered by file name.
+    /// Strings not contained in a file are taken from `base`.
+    pub fn from_locale_dir<P: AsRef<Utf8Path>>(dir: P, base: &FileInfo) -> Result<Vec<FileInfo>, LocaleError> {
+        let dir = dir.as_ref();
+        let error = |path: &Utf8Path, message: String| LocaleError {
+            path: path.to_owned(),
+            line: 0,
+            message,
+        };
+        let mut paths: Vec<Utf8PathBuf> = dir
+            .read_dir_utf8()
+            .map_err(|io| error(dir, format!("can not read the directory: {io}")))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.into_path()))
+            .filter(|path| matches!(path.extension(), Some("toml" | "ftl")))
+            .collect();
+        paths.sort();
+
+        let mut file_infos: Vec<FileInfo> = Vec::new();
+        for path in paths {
+            let locale = path.file_stem().unwrap_or_default();
+            let lang: Language = locale.parse().map_err(|message| error(&path, message))?;
+            let content = std::fs::read_to_string(&path).map_err(|io| error(&path, format!("can not read the file: {io}")))?;
+            let translation = match path.extension() {
+                Some("toml") => parse_toml(&content),
+                _ => parse_ftl(&content),
+            }
+            .map_err(|(line, message)| LocaleError {
+                path: path.clone(),
+                line,
+                message,
+            })?;
+            let charset = match translation.charset {
+                Some(charset) => charset.parse().map_err(|message| error(&path, message))?,
+                None => CharacterSet::Unicode,
+            };
+            if file_infos
+                .iter()
+                .any(|file_info| file_info.lang == lang && file_info.charset == charset)
+            {
+                return Err(error(&path, format!("there is more than one {lang:?}/{charset:?} translation")));
+            }
+
+            let mut file_info = base.clone();
+            file_info.lang = lang;
+            file_info.charset = charset;
+            for (key, value) in translation.strings {
+                file_info.insert(key, value.into());
+            }
+            file_infos.push(file_info);
+        }
+        Ok(file_infos)
+    }
+}
+
+impl VersionInfo {
+    /// Replaces the string tables by the ones loaded from the translation files in `dir`,
+    /// see [`FileInfo::from_locale_dir`]. The first current string table is used as the base.
+    /// An `Auto` `VarFileInfo` translation list follows the new string tables.
+    pub fn with_locales<P: AsRef<Utf8Path>>(mut self, dir: P) -> Result<Self, LocaleError> {
+        let base = self
+            .file_info
+            .first()
+            .cloned()
+            .unwrap_or_else(|| FileInfo::empty(Language::USEnglish, CharacterSet::Unicode));
+        self.file_info = FileInfo::from_locale_dir(dir, &base)?;
+        Ok(self)
+    }
+}
+
+fn parse_toml(content: &str) -> Result<LocaleFile, (usize, String)> {
+    let line = |position: usize| content[..position].matches('\n').count() + 1;
+    let table: std::collections::BTreeMap<String, toml::Spanned<toml::Value>> =
+        toml::from_str(content).map_err(|error: toml::de::Error| {
+            (error.span().map_or(0, |span| line(span.start)), error.message().to_owned())
+        })?;
+    let mut translation = LocaleFile {
+        charset: None,
+        strings: Vec::new(),
+    };
+    for (key, value) in table {
+        let start = value.span().start;
+        let value = match value.into_inner() {
+            toml::Value::String(value) => value,
+            toml::Value::Integer(value) if key == "-charset" => value.to_string(),
+            value => return Err((line(start), format!("`{key}` must be a string, not {}", value.type_str()))),
+        };
+        if key == "-charset" {
+            translation.charset = Some(value);
+        } else {
+            translation.strings.push((key, value));
+        }
+    }
+    Ok(translation)
+}
+
+/// parses the subset of fluent consisting of messages with plain text
+fn parse_ftl(content: &str) -> Result<LocaleFile, (usize, String)> {
+    let mut translation = LocaleFile {
+        charset: None,
+        strings: Vec::new(),
+    };
+    // whether the last entry was the `-charset` term, which has no continuation lines
+    let mut after_term = false;
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with([' ', '\t']) {
+            let continuation = line.trim();
+            if continuation.starts_with('.') {
+                return Err((line_number, "attributes are not supported".into()));
+            }
+            if after_term {
+                return Err((line_number, "the `-charset` term must be on one line".into()));
+            }
+            let Some((_, value)) = translation.strings.last_mut() else {
+                return Err((line_number, "continuation line without a message".into()));
+            };
+            if !value.is_empty() {
+                value.push('\n');
+            }
+            value.push_str(continuation);
+        } else {
+            let Some((key, value)) = line.split_once('=') else {
+                return Err((line_number, "expected `key = value`".into()));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            after_term = key == "-charset";
+            if after_term {
+                translation.charset = Some(value.to_owned());
+                continue;
+            }
+            let valid = key.starts_with(|c: char| c.is_ascii_alphabetic())
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid {
+                return Err((line_number, format!("invalid message identifier `{key}`")));
+            }
+            translation.strings.push((key.to_owned(), value.to_owned()));
+        }
+        if line.contains(['{', '}']) {
+            return Err((line_number, "placeables are not supported".into()));
+        }
+    }
+    Ok(translation)
+}
@@ -0,0 +1,154 @@
+//! `CompanyName` and `LegalCopyright` derived from the package metadata
+use super::*;
+
+/// Where the `CompanyName` string is taken from
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum CompanySource {
+    /// no company name
+    #[default]
+    None,
+    /// the given name
+    Fixed(String),
+    /// the first entry of `CARGO_PKG_AUTHORS` without its e-mail address
+    FirstAuthor,
+    /// all entries of `CARGO_PKG_AUTHORS` without their e-mail addresses
+    Authors,
+    /// the value of the given environment variable, e.g. one set by a CI system.
+    /// Use `rerun_if_changed` to rebuild when it changes
+    Env(String),
+}
+
+impl CompanySource {
+    /// the company name, if the source provides one
+    pub fn resolve(&self) -> Option<String> {
+        let company = match self {
+            Self::None => return None,
+            Self::Fixed(company) => company.clone(),
+            Self::FirstAuthor => authors(&var("CARGO_PKG_AUTHORS").unwrap_or_default())
+                .into_iter()
+                .next()?,
+            Self::Authors => authors(&var("CARGO_PKG_AUTHORS").unwrap_or_default()).join(", "),
+            Self::Env(variable) => var(variable).ok()?,
+        };
+        (!company.is_empty()).then_some(company)
+    }
+
+    /// Tells cargo to rerun the build script when the environment variable of `Env` changes.
+    /// Not done by `resolve`, as the first `rerun-if` directive stops cargo from rerunning the build script
+    /// on every change of the package, so the other inputs of the build script have to be declared as well
+    pub fn rerun_if_changed(&self) {
+        if let Self::Env(variable) = self {
+            println!("cargo:rerun-if-env-changed={variable}");
+        }
+    }
+}
+
+/// Where the `LegalCopyright` string is taken from
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum CopyrightSource {
+    /// no copyright notice
+    #[default]
+    None,
+    /// the given notice
+    Fixed(String),
+    /// `Copyright (c) YEAR AUTHORS`, followed by the license if `CARGO_PKG_LICENSE` is set.
+    /// The year is taken from `SOURCE_DATE_EPOCH` if set, keeping builds reproducible.
+    /// Use `rerun_if_changed` to rebuild when it changes
+    Authors,
+    /// like `Authors`, but with a year range starting at the given year
+    AuthorsSince(u16),
+}
+
+impl CopyrightSource {
+    /// the copyright notice, if the source provides one
+    pub fn resolve(&self) -> Option<String> {
+        self.resolve_with(
+            &var("CARGO_PKG_AUTHORS").unwrap_or_default(),
+            &var("CARGO_PKG_LICENSE").unwrap_or_default(),
+            var("SOURCE_DATE_EPOCH").ok().as_deref(),
+        )
+    }
+
+    /// like `resolve`, but with the values of `CARGO_PKG_AUTHORS`, `CARGO_PKG_LICENSE` and `SOURCE_DATE_EPOCH`
+    pub(crate) fn resolve_with(&self, authors_var: &str, license: &str, epoch: Option<&str>) -> Option<String> {
+        let since = match self {
+            Self::None => return None,
+            Self::Fixed(notice) => return Some(notice.clone()),
+            Self::Authors => None,
+            Self::AuthorsSince(year) => Some(*year),
+        };
+        let authors = authors(authors_var).join(", ");
+        if authors.is_empty() {
+            return None;
+        }
+        let year = epoch_year(epoch);
+        let years = match since {
+            Some(since) if since < year => format!("{since}-{year}"),
+            _ => year.to_string(),
+        };
+        Some(copyright_notice(&years, &authors, license))
+    }
+
+    /// Tells cargo to rerun the build script when `SOURCE_DATE_EPOCH` changes, see `CompanySource::rerun_if_changed`
+    pub fn rerun_if_changed(&self) {
+        if matches!(self, Self::Authors | Self::AuthorsSince(_)) {
+            println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+        }
+    }
+}
+
+/// splits `CARGO_PKG_AUTHORS` and removes the e-mail addresses
+pub(crate) fn authors(authors: &str) -> Vec<String> {
+    authors
+        .split(':')
+        .map(|author| match author.split_once('<') {
+            Some((name, _)) => name.trim(),
+            None => author.trim(),
+        })
+        .filter(|author| !author.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+pub(crate) fn copyright_notice(years: &str, authors: &str, license: &str) -> String {
+    if license.is_empty() {
+        format!("Copyright (c) {years} {authors}")
+    } else {
+        format!("Copyright (c) {years} {authors}, licensed under {license}")
+    }
+}
+
+/// the year of the `SOURCE_DATE_EPOCH` value `epoch`, the current year if it is `None`.
+/// An invalid value is reported as build script warning and the current year is used
+pub(crate) fn epoch_year(epoch: Option<&str>) -> u16 {
+    let epoch = epoch.and_then(|epoch| match epoch.trim().parse() {
+        Ok(seconds) => Some(seconds),
+        Err(_) => {
+            println!("cargo:warning=SOURCE_DATE_EPOCH `{epoch}` is not a unix timestamp, using the current time");
+            None
+        }
+    });
+    let seconds = epoch.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs())
+    });
+    year_of(seconds)
+}
+
+/// the year of a unix timestamp in UTC
+pub(crate) fn year_of(seconds: u64) -> u16 {
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let year = year_of_era + era * 400 + i64::from(month_index >= 10);
+    year as u16
+}
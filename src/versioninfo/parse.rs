@@ -0,0 +1,622 @@
+//! Parser reading the VERSIONINFO statement of an existing resource script
+use super::*;
+use camino::Utf8Path;
+
+/// Error returned when a resource script can not be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// line of the resource script the error occurred in, starting at 1. 0 if the error is not related to a line
+    pub line: usize,
+    pub message: String,
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Number(u32),
+    String(String),
+    Symbol(char),
+}
+
+impl core::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Word(word) => write!(f, "`{word}`"),
+            Self::Number(number) => write!(f, "`{number}`"),
+            Self::String(string) => write!(f, "\"{string}\""),
+            Self::Symbol(symbol) => write!(f, "`{symbol}`"),
+        }
+    }
+}
+
+/// splits a resource script into tokens together with their line number.
+/// Comments and preprocessor directives are skipped,
+/// conditional directives fail as the parser does not know which branch applies.
+fn tokenize(script: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = script.chars().peekable();
+    let mut line = 1;
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() || c == '\u{FEFF}' => continue,
+            '#' if line_start => {
+                // preprocessor directive, e.g. #include or #pragma
+                let mut directive = String::new();
+                while let Some(c) = chars.next_if(|c| *c != '\n') {
+                    directive.push(c);
+                }
+                let name = directive
+                    .trim_start()
+                    .split(|c: char| !c.is_ascii_alphabetic())
+                    .next()
+                    .unwrap_or_default();
+                if ["if", "ifdef", "ifndef", "elif", "else", "endif"].contains(&name) {
+                    return Err(ParseError {
+                        line,
+                        message: format!("conditional directive `#{name}` is not supported"),
+                    });
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            last = c;
+                        }
+                        None => {
+                            return Err(ParseError {
+                                line,
+                                message: "unterminated comment".into(),
+                            })
+                        }
+                    }
+                }
+            }
+            'L' if chars.peek() == Some(&'"') => {
+                chars.next();
+                tokens.push((line, Token::String(read_string(&mut chars, &mut line)?)));
+            }
+            '"' => tokens.push((line, Token::String(read_string(&mut chars, &mut line)?))),
+            c if c.is_ascii_digit() => {
+                let mut literal = String::from(c);
+                while let Some(c) = chars.next_if(char::is_ascii_alphanumeric) {
+                    literal.push(c);
+                }
+                let digits = literal.trim_end_matches(['L', 'l', 'U', 'u']);
+                let number = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16),
+                    None => digits.parse(),
+                }
+                .map_err(|_| ParseError {
+                    line,
+                    message: format!("invalid number `{literal}`"),
+                })?;
+                tokens.push((line, Token::Number(number)));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    word.push(c);
+                }
+                tokens.push((line, Token::Word(word)));
+            }
+            c => tokens.push((line, Token::Symbol(c))),
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+/// reads a quoted string after the opening quote and resolves escape sequences
+fn read_string(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    line: &mut usize,
+) -> Result<String, ParseError> {
+    let mut string = String::new();
+    loop {
+        match chars.next() {
+            Some('"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                string.push('"');
+            }
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some('n') => string.push('\n'),
+                Some('r') => string.push('\r'),
+                Some('t') => string.push('\t'),
+                Some('a') => string.push('\u{7}'),
+                Some('0') => string.push('\0'),
+                Some('x') => {
+                    let mut hex = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_hexdigit() && hex.len() < 4) {
+                        hex.push(c);
+                    }
+                    let c = u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| ParseError {
+                            line: *line,
+                            message: format!(r"invalid escape sequence `\x{hex}`"),
+                        })?;
+                    string.push(c);
+                }
+                Some(c) => string.push(c),
+                None => break,
+            },
+            Some(c) => {
+                if c == '\n' {
+                    *line += 1;
+                }
+                string.push(c);
+            }
+            None => break,
+        }
+    }
+    Err(ParseError {
+        line: *line,
+        message: "unterminated string".into(),
+    })
+}
+
+/// numeric value of the `winver.h` macros usable in the fixed part of a VERSIONINFO
+fn macro_value(name: &str) -> Option<u32> {
+    Some(match name {
+        "VS_FFI_FILEFLAGSMASK" => 0x3F,
+        "VS_FF_DEBUG" => 0x01,
+        "VS_FF_PRERELEASE" => 0x02,
+        "VS_FF_PATCHED" => 0x04,
+        "VS_FF_PRIVATEBUILD" => 0x08,
+        "VS_FF_INFOINFERRED" => 0x10,
+        "VS_FF_SPECIALBUILD" => 0x20,
+        "VOS_UNKNOWN" | "VOS__BASE" => 0x0000_0000,
+        "VOS_DOS" => 0x0001_0000,
+        "VOS_OS216" => 0x0002_0000,
+        "VOS_OS232" => 0x0003_0000,
+        "VOS_NT" => 0x0004_0000,
+        "VOS_WINCE" => 0x0005_0000,
+        "VOS__WINDOWS16" => 0x0000_0001,
+        "VOS__PM16" => 0x0000_0002,
+        "VOS__PM32" => 0x0000_0003,
+        "VOS__WINDOWS32" => 0x0000_0004,
+        "VOS_DOS_WINDOWS16" => 0x0001_0001,
+        "VOS_DOS_WINDOWS32" => 0x0001_0004,
+        "VOS_OS216_PM16" => 0x0002_0002,
+        "VOS_OS232_PM32" => 0x0003_0003,
+        "VOS_NT_WINDOWS32" => 0x0004_0004,
+        "VFT_UNKNOWN" | "VFT2_UNKNOWN" => 0,
+        "VFT_APP" => 1,
+        "VFT_DLL" => 2,
+        "VFT_DRV" => 3,
+        "VFT_FONT" => 4,
+        "VFT_VXD" => 5,
+        "VFT_STATIC_LIB" => 7,
+        "VFT2_DRV_PRINTER" | "VFT2_FONT_RASTER" => 1,
+        "VFT2_DRV_KEYBOARD" | "VFT2_FONT_VECTOR" => 2,
+        "VFT2_DRV_LANGUAGE" | "VFT2_FONT_TRUETYPE" => 3,
+        "VFT2_DRV_DISPLAY" => 4,
+        "VFT2_DRV_MOUSE" => 5,
+        "VFT2_DRV_NETWORK" => 6,
+        "VFT2_DRV_SYSTEM" => 7,
+        "VFT2_DRV_INSTALLABLE" => 8,
+        "VFT2_DRV_SOUND" => 9,
+        "VFT2_DRV_COMM" => 10,
+        "VFT2_DRV_INPUTMETHOD" => 11,
+        "VFT2_DRV_VERSIONED_PRINTER" => 12,
+        _ => return None,
+    })
+}
+
+/// a value of the fixed part: either a single macro name or a computed number
+enum Expression {
+    Name(String),
+    Number(u32),
+}
+
+impl Expression {
+    fn value(&self) -> u32 {
+        match self {
+            Self::Name(name) => macro_value(name).unwrap_or_default(),
+            Self::Number(number) => *number,
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(1, |(line, _)| *line)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError {
+            line: self.line(),
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Result<Token, ParseError> {
+        match self.tokens.get(self.position) {
+            Some((_, token)) => {
+                self.position += 1;
+                Ok(token.clone())
+            }
+            None => self.error("unexpected end of script"),
+        }
+    }
+
+    fn next_if(&mut self, expected: &Token) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), ParseError> {
+        match self.next()? {
+            token if &token == expected => Ok(()),
+            token => {
+                self.position -= 1;
+                self.error(format!("expected {expected}, found {token}"))
+            }
+        }
+    }
+
+    fn word(&mut self) -> Result<String, ParseError> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            token => {
+                self.position -= 1;
+                self.error(format!("expected a keyword, found {token}"))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        match self.next()? {
+            Token::String(string) => Ok(string),
+            token => {
+                self.position -= 1;
+                self.error(format!("expected a string, found {token}"))
+            }
+        }
+    }
+
+    fn begin(&mut self) -> Result<(), ParseError> {
+        if !self.next_if(&Token::Symbol('{')) {
+            self.expect(&Token::Word("BEGIN".into()))?;
+        }
+        Ok(())
+    }
+
+    fn end(&mut self) -> bool {
+        self.next_if(&Token::Symbol('}')) || self.next_if(&Token::Word("END".into()))
+    }
+
+    /// parses `TERM [| TERM]...` where every term is a number or a `winver.h` macro
+    fn expression(&mut self) -> Result<Expression, ParseError> {
+        let mut terms = Vec::new();
+        loop {
+            let term = match self.next()? {
+                Token::Number(number) => Expression::Number(number),
+                Token::Word(name) if macro_value(&name).is_some() => Expression::Name(name),
+                Token::Word(name) => {
+                    self.position -= 1;
+                    return self.error(format!("unknown constant `{name}`"));
+                }
+                Token::Symbol('(') => {
+                    let term = self.expression()?;
+                    self.expect(&Token::Symbol(')'))?;
+                    Expression::Number(term.value())
+                }
+                token => {
+                    self.position -= 1;
+                    return self.error(format!("expected a number, found {token}"));
+                }
+            };
+            terms.push(term);
+            if !(self.next_if(&Token::Symbol('|')) || self.next_if(&Token::Symbol('+'))) {
+                break;
+            }
+        }
+        Ok(match terms.pop() {
+            Some(term) if terms.is_empty() => term,
+            Some(term) => Expression::Number(terms.iter().fold(term.value(), |value, term| value | term.value())),
+            None => Expression::Number(0),
+        })
+    }
+
+    fn version(&mut self) -> Result<Version, ParseError> {
+        let mut parts = [0; 4];
+        for (i, part) in parts.iter_mut().enumerate() {
+            if i > 0 && !self.next_if(&Token::Symbol(',')) {
+                break;
+            }
+            let value = self.expression()?.value();
+            *part = u16::try_from(value).or_else(|_| self.error(format!("version component {value} is too large")))?;
+        }
+        Ok(Version(parts[0], parts[1], parts[2], parts[3]))
+    }
+
+    /// parses the statement following the VERSIONINFO keyword
+    fn version_info(&mut self) -> Result<VersionInfo, ParseError> {
+        let mut version_info = VersionInfo {
+            file_version: Version(0, 0, 0, 0),
+            product_version: Version(0, 0, 0, 0),
+            file_flag_mask: FileFlagMask::Win16,
+            file_flags: FileFlags::default(),
+            file_os: FileOS::Windows32,
+            file_type: FileType::App,
+            file_info: Vec::new(),
+            var_file_info: VarFileInfo::Auto,
+        };
+        let mut translations = None;
+        let mut file_type = Expression::Number(1);
+        let mut file_subtype = Expression::Number(0);
+
+        while let Some(Token::Word(statement)) = self.peek() {
+            let statement = statement.clone();
+            if statement == "BEGIN" {
+                break;
+            }
+            self.position += 1;
+            match statement.as_str() {
+                "FILEVERSION" => version_info.file_version = self.version()?,
+                "PRODUCTVERSION" => version_info.product_version = self.version()?,
+                "FILEFLAGSMASK" => {
+                    version_info.file_flag_mask = FileFlagMask::from_value(self.expression()?.value())
+                }
+                "FILEFLAGS" => version_info.file_flags = FileFlags::from_value(self.expression()?.value()),
+                "FILEOS" => version_info.file_os = file_os(self.expression()?),
+                "FILETYPE" => file_type = self.expression()?,
+                "FILESUBTYPE" => file_subtype = self.expression()?,
+                _ => {
+                    self.position -= 1;
+                    return self.error(format!("unknown VERSIONINFO statement `{statement}`"));
+                }
+            }
+        }
+        version_info.file_type = file_type_from(file_type, file_subtype);
+
+        self.begin()?;
+        while !self.end() {
+            self.expect(&Token::Word("BLOCK".into()))?;
+            match self.string()?.as_str() {
+                "StringFileInfo" => {
+                    self.begin()?;
+                    while !self.end() {
+                        version_info.file_info.push(self.string_table()?);
+                    }
+                }
+                "VarFileInfo" => {
+                    self.begin()?;
+                    while !self.end() {
+                        self.expect(&Token::Word("VALUE".into()))?;
+                        let key = self.string()?;
+                        let mut values = Vec::new();
+                        while self.next_if(&Token::Symbol(',')) {
+                            values.push(self.expression()?.value());
+                        }
+                        if key != "Translation" {
+                            continue;
+                        }
+                        if values.len() % 2 != 0 {
+                            return self.error("the translations must be pairs of language and codepage");
+                        }
+                        let list = translations.get_or_insert_with(Vec::new);
+                        for pair in values.chunks_exact(2) {
+                            let (lang, codepage) = (pair[0] as u16, pair[1] as u16);
+                            let Some(translation) = Translation::from_ids(lang, codepage) else {
+                                return self.error(format!("unknown translation {lang:#06X}, {codepage}"));
+                            };
+                            list.push(translation);
+                        }
+                    }
+                }
+                block => return self.error(format!("unknown block \"{block}\"")),
+            }
+        }
+        version_info.var_file_info = VarFileInfo::from_translations(translations, &version_info.file_info);
+        Ok(version_info)
+    }
+
+    /// parses a `BLOCK "lang-charset"` inside the StringFileInfo block
+    fn string_table(&mut self) -> Result<FileInfo, ParseError> {
+        self.expect(&Token::Word("BLOCK".into()))?;
+        let name = self.string()?;
+        let (lang, charset) = match (name.get(..4), name.get(4..)) {
+            (Some(lang), Some(charset)) if name.len() == 8 => (lang, charset),
+            _ => return self.error(format!("invalid string table name \"{name}\"")),
+        };
+        let Some(lang) = Language::ALL
+            .into_iter()
+            .find(|known| known.to_hex().eq_ignore_ascii_case(lang))
+        else {
+            return self.error(format!("unknown language id {lang}"));
+        };
+        let Some(charset) = CharacterSet::ALL
+            .into_iter()
+            .find(|known| known.to_hex().eq_ignore_ascii_case(charset))
+        else {
+            return self.error(format!("unknown character set id {charset}"));
+        };
+
+        let mut file_info = FileInfo::empty(lang, charset);
+
+        self.begin()?;
+        while !self.end() {
+            self.expect(&Token::Word("VALUE".into()))?;
+            let key = self.string()?;
+            let mut value = String::new();
+            if self.next_if(&Token::Symbol(',')) {
+                // adjacent strings are concatenated
+                while let Some(Token::String(_)) = self.peek() {
+                    value.push_str(&self.string()?);
+                }
+            }
+            let value = RCString::from(value.strip_suffix('\0').unwrap_or(&value));
+            file_info.insert(key, value);
+        }
+        Ok(file_info)
+    }
+}
+
+fn file_os(expression: Expression) -> FileOS {
+    const NAMED: [FileOS; 16] = [
+        FileOS::Unknown,
+        FileOS::Dos,
+        FileOS::OS216Bit,
+        FileOS::OS232Bit,
+        FileOS::NT,
+        FileOS::WinCE,
+        FileOS::Base,
+        FileOS::Windows16,
+        FileOS::PM16,
+        FileOS::PM32,
+        FileOS::Windows32,
+        FileOS::DosWindows16,
+        FileOS::DosWindows32,
+        FileOS::OS216BitPM16,
+        FileOS::OS232BitPM32,
+        FileOS::NTWindows32,
+    ];
+    match expression {
+        Expression::Name(name) => NAMED
+            .into_iter()
+            .find(|os| os.get_value() == name)
+            .unwrap_or(FileOS::Unknown),
+        Expression::Number(number) => FileOS::from_value(number),
+    }
+}
+
+fn file_type_from(file_type: Expression, subtype: Expression) -> FileType {
+    let Expression::Name(name) = file_type else {
+        return FileType::from_values(file_type.value(), subtype.value());
+    };
+    match name.as_str() {
+        "VFT_APP" => FileType::App,
+        "VFT_DLL" => FileType::Dll,
+        "VFT_DRV" => FileType::Driver(match subtype {
+            Expression::Name(name) => match name.as_str() {
+                "VFT2_DRV_PRINTER" => SubTypeDriver::Printer,
+                "VFT2_DRV_KEYBOARD" => SubTypeDriver::Keyboard,
+                "VFT2_DRV_LANGUAGE" => SubTypeDriver::Language,
+                "VFT2_DRV_DISPLAY" => SubTypeDriver::Display,
+                "VFT2_DRV_MOUSE" => SubTypeDriver::Mouse,
+                "VFT2_DRV_NETWORK" => SubTypeDriver::Network,
+                "VFT2_DRV_SYSTEM" => SubTypeDriver::System,
+                "VFT2_DRV_INSTALLABLE" => SubTypeDriver::Installable,
+                "VFT2_DRV_SOUND" => SubTypeDriver::Sound,
+                "VFT2_DRV_COMM" => SubTypeDriver::Comm,
+                "VFT2_DRV_INPUTMETHOD" => SubTypeDriver::InputMethod,
+                "VFT2_DRV_VERSIONED_PRINTER" => SubTypeDriver::VersionedPrinter,
+                _ => SubTypeDriver::Unknown,
+            },
+            Expression::Number(number) => SubTypeDriver::from_value(number),
+        }),
+        "VFT_FONT" => FileType::Font(match subtype {
+            Expression::Name(name) => match name.as_str() {
+                "VFT2_FONT_RASTER" => SubTypeFont::RasterFont,
+                "VFT2_FONT_VECTOR" => SubTypeFont::VectorFont,
+                "VFT2_FONT_TRUETYPE" => SubTypeFont::TrueTypeFont,
+                _ => SubTypeFont::Custom(macro_value(&name).unwrap_or_default()),
+            },
+            Expression::Number(number) => SubTypeFont::from_value(number),
+        }),
+        "VFT_VXD" => FileType::VXD,
+        "VFT_STATIC_LIB" => FileType::StaticLibrary,
+        _ => FileType::Unknown,
+    }
+}
+
+impl VersionInfo {
+    /// Parses the VERSIONINFO statement of a resource script.
+    /// Other resources in the script are ignored.
+    /// `var_file_info` is `Auto` if the translations match the string tables.
+    pub fn from_rc(script: &str) -> Result<Self, ParseError> {
+        let tokens = tokenize(script)?;
+        let Some(position) = tokens
+            .iter()
+            .position(|(_, token)| token == &Token::Word("VERSIONINFO".into()))
+        else {
+            return Err(ParseError {
+                line: 1,
+                message: "the script does not contain a VERSIONINFO statement".into(),
+            });
+        };
+        let mut parser = Parser {
+            tokens,
+            position: position + 1,
+        };
+        parser.version_info()
+    }
+
+    /// Reads and parses a resource script saved either as UTF-8 or as UTF-16 with byte order mark.
+    /// Fails with line 0 if the file can not be read
+    pub fn from_rc_file<P: AsRef<Utf8Path>>(path: P) -> Result<Self, ParseError> {
+        let bytes = std::fs::read(path.as_ref()).map_err(|error| ParseError {
+            line: 0,
+            message: format!("could not read {}: {error}", path.as_ref()),
+        })?;
+        let script = match bytes.as_slice() {
+            [0xFF, 0xFE, rest @ ..] => String::from_utf16_lossy(
+                &rest
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>(),
+            ),
+            [0xFE, 0xFF, rest @ ..] => String::from_utf16_lossy(
+                &rest
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>(),
+            ),
+            bytes => String::from_utf8_lossy(bytes).into_owned(),
+        };
+        Self::from_rc(&script)
+    }
+}
+
+impl core::str::FromStr for VersionInfo {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_rc(s)
+    }
+}
@@ -0,0 +1,208 @@
+//! Consistency checks run before linking version information
+use super::*;
+
+/// How severe a [`Diagnostic`] is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// the version information is linked anyway
+    Warning,
+    /// the version information is not linked
+    Error,
+}
+
+/// A problem found by [`VersionInfo::validate`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// `FileFlags::privatebuild` is set, but the string table has no `PrivateBuild` string
+    MissingPrivateBuild { lang: Language, charset: CharacterSet },
+    /// the string table has a `PrivateBuild` string, but `FileFlags::privatebuild` is not set
+    UnexpectedPrivateBuild { lang: Language, charset: CharacterSet },
+    /// `FileFlags::specialbuild` is set, but the string table has no `SpecialBuild` string
+    MissingSpecialBuild { lang: Language, charset: CharacterSet },
+    /// the string table has a `SpecialBuild` string, but `FileFlags::specialbuild` is not set
+    UnexpectedSpecialBuild { lang: Language, charset: CharacterSet },
+    /// the `FileVersion` string does not match `VersionInfo::file_version`
+    FileVersionMismatch {
+        lang: Language,
+        charset: CharacterSet,
+        string: String,
+        version: Version,
+    },
+    /// the `ProductVersion` string does not match `VersionInfo::product_version`
+    ProductVersionMismatch {
+        lang: Language,
+        charset: CharacterSet,
+        string: String,
+        version: Version,
+    },
+    /// two string tables use the same language and character set
+    DuplicateTranslation { lang: Language, charset: CharacterSet },
+    /// a string contains a character that does not exist in the character set of its string table.
+    /// Only reported if the script is encoded in the codepages of the character sets, i.e. with `ScriptEncoding::Utf8`
+    UnencodableString {
+        lang: Language,
+        charset: CharacterSet,
+        key: String,
+        character: char,
+    },
+    /// the explicit `VarFileInfo` translations do not list the string table
+    MissingTranslation { lang: Language, charset: CharacterSet },
+}
+
+impl Diagnostic {
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::MissingPrivateBuild { .. }
+            | Self::MissingSpecialBuild { .. }
+            | Self::DuplicateTranslation { .. }
+            | Self::UnencodableString { .. } => Severity::Error,
+            Self::UnexpectedPrivateBuild { .. }
+            | Self::UnexpectedSpecialBuild { .. }
+            | Self::FileVersionMismatch { .. }
+            | Self::ProductVersionMismatch { .. }
+            | Self::MissingTranslation { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingPrivateBuild { lang, charset } => write!(
+                f,
+                "the private build flag is set, but the {lang:?}/{charset:?} string table has no PrivateBuild string"
+            ),
+            Self::UnexpectedPrivateBuild { lang, charset } => write!(
+                f,
+                "the {lang:?}/{charset:?} string table has a PrivateBuild string, but the private build flag is not set"
+            ),
+            Self::MissingSpecialBuild { lang, charset } => write!(
+                f,
+                "the special build flag is set, but the {lang:?}/{charset:?} string table has no SpecialBuild string"
+            ),
+            Self::UnexpectedSpecialBuild { lang, charset } => write!(
+                f,
+                "the {lang:?}/{charset:?} string table has a SpecialBuild string, but the special build flag is not set"
+            ),
+            Self::FileVersionMismatch {
+                lang,
+                charset,
+                string,
+                version,
+            } => write!(
+                f,
+                "the FileVersion \"{string}\" of the {lang:?}/{charset:?} string table does not match the file version {}",
+                String::from(*version)
+            ),
+            Self::ProductVersionMismatch {
+                lang,
+                charset,
+                string,
+                version,
+            } => write!(
+                f,
+                "the ProductVersion \"{string}\" of the {lang:?}/{charset:?} string table does not match the product version {}",
+                String::from(*version)
+            ),
+            Self::DuplicateTranslation { lang, charset } => write!(
+                f,
+                "there is more than one {lang:?}/{charset:?} string table"
+            ),
+            Self::UnencodableString {
+                lang,
+                charset,
+                key,
+                character,
+            } => write!(
+                f,
+                "the {key} string of the {lang:?}/{charset:?} string table contains `{character}`, which does not exist in codepage {}",
+                charset.codepage()
+            ),
+            Self::MissingTranslation { lang, charset } => write!(
+                f,
+                "the {lang:?}/{charset:?} string table is not listed in the VarFileInfo translations"
+            ),
+        }
+    }
+}
+
+/// checks whether the leading numeric components of a version string match `version`.
+/// Strings not starting with a number, like "To be decided", are not checked.
+fn version_matches(string: &str, version: Version) -> bool {
+    let numeric = [version.0, version.1, version.2, version.3];
+    for (component, expected) in string.split('.').zip(numeric) {
+        let digits: String = component.chars().take_while(char::is_ascii_digit).collect();
+        match digits.parse::<u16>() {
+            Ok(value) if value != expected => return false,
+            Ok(_) if digits.len() != component.len() => return true,
+            Ok(_) => {}
+            Err(_) => return true,
+        }
+    }
+    true
+}
+
+impl VersionInfo {
+    /// Checks the version information for inconsistencies,
+    /// e.g. a set private build flag without a `PrivateBuild` string.
+    /// [`VersionInfo::link`] refuses to link if an error is found.
+    /// The strings are checked against the `ScriptEncoding` selected with `set_script_encoding`
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        self.validate_for(crate::link::script_encoding())
+    }
+
+    /// like `validate`, but for a script written using `encoding`
+    pub(crate) fn validate_for(&self, encoding: ScriptEncoding) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        // only scripts encoded in the codepages of the character sets can miss characters
+        let codepages = encoding.resolve() == ScriptEncoding::Utf8;
+        let translations = self.translations();
+        for (i, file_info) in self.file_info.iter().enumerate() {
+            let (lang, charset) = (file_info.lang, file_info.charset);
+
+            match (self.file_flags.privatebuild, &file_info.private_build) {
+                (true, None) => diagnostics.push(Diagnostic::MissingPrivateBuild { lang, charset }),
+                (false, Some(_)) => diagnostics.push(Diagnostic::UnexpectedPrivateBuild { lang, charset }),
+                _ => {}
+            }
+            match (self.file_flags.specialbuild, &file_info.special_build) {
+                (true, None) => diagnostics.push(Diagnostic::MissingSpecialBuild { lang, charset }),
+                (false, Some(_)) => diagnostics.push(Diagnostic::UnexpectedSpecialBuild { lang, charset }),
+                _ => {}
+            }
+
+            if !version_matches(file_info.file_version.as_str(), self.file_version) {
+                diagnostics.push(Diagnostic::FileVersionMismatch {
+                    lang,
+                    charset,
+                    string: file_info.file_version.as_str().to_owned(),
+                    version: self.file_version,
+                });
+            }
+            if !version_matches(file_info.product_version.as_str(), self.product_version) {
+                diagnostics.push(Diagnostic::ProductVersionMismatch {
+                    lang,
+                    charset,
+                    string: file_info.product_version.as_str().to_owned(),
+                    version: self.product_version,
+                });
+            }
+
+            if let Some(diagnostic) = file_info.unencodable_string().filter(|_| codepages) {
+                diagnostics.push(diagnostic);
+            }
+            if !translations.contains(&Translation::new(lang, charset)) {
+                diagnostics.push(Diagnostic::MissingTranslation { lang, charset });
+            }
+
+            let duplicate = self.file_info[..i]
+                .iter()
+                .any(|other| other.lang == lang && other.charset == charset);
+            let reported = diagnostics.contains(&Diagnostic::DuplicateTranslation { lang, charset });
+            if duplicate && !reported {
+                diagnostics.push(Diagnostic::DuplicateTranslation { lang, charset });
+            }
+        }
+        diagnostics
+    }
+}